@@ -0,0 +1,16 @@
+use vergen_gitcl::{Build, Emitter, Gitcl, Rustc};
+
+// Embeds the git commit, build date and rustc version for `skills version`
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let build = Build::builder().build_date(true).build();
+    let git = Gitcl::builder().sha(true).build();
+    let rustc = Rustc::builder().semver(true).build();
+
+    Emitter::default()
+        .add_instructions(&build)?
+        .add_instructions(&git)?
+        .add_instructions(&rustc)?
+        .emit()?;
+
+    Ok(())
+}
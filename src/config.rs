@@ -0,0 +1,202 @@
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::github::{GitHubUrlParser, expand_shorthand};
+use crate::installer::TargetType;
+use crate::models::Config;
+
+/// Project file declaring where `skills install` puts skills when no target is given
+pub const PROJECT_CONFIG_FILE: &str = ".skills.toml";
+
+/// Contents of a project's `.skills.toml`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Targets whose project-local skills directories receive the skill
+    #[serde(default)]
+    pub targets: Vec<TargetType>,
+    /// Extra directories, relative to the project root, that receive the skill
+    #[serde(default)]
+    pub dirs: Vec<PathBuf>,
+    /// Directory holding the `.skills.toml`
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+/// Reads the `.skills.toml` in `start_dir` or the nearest parent directory that has one,
+/// returning `None` when there is none
+pub fn load_project_config(start_dir: &Path) -> Result<Option<ProjectConfig>> {
+    let Some(project_dir) = start_dir
+        .ancestors()
+        .find(|dir| dir.join(PROJECT_CONFIG_FILE).is_file())
+    else {
+        return Ok(None);
+    };
+    let path = project_dir.join(PROJECT_CONFIG_FILE);
+
+    let content = fs::read_to_string(&path).context("Failed to read .skills.toml")?;
+    let mut config: ProjectConfig =
+        toml::from_str(&content).context("Failed to parse .skills.toml")?;
+
+    if config.targets.is_empty() && config.dirs.is_empty() {
+        return Err(anyhow!(
+            "{} declares no targets or dirs",
+            PROJECT_CONFIG_FILE
+        ));
+    }
+
+    config.dirs = config
+        .dirs
+        .into_iter()
+        .map(|dir| project_dir.join(dir))
+        .collect();
+    config.root = project_dir.to_path_buf();
+    Ok(Some(config))
+}
+
+/// Trait for accessing the CLI configuration
+pub trait ConfigStorage {
+    fn load(&self) -> Result<Config>;
+    fn save(&self, config: &Config) -> Result<()>;
+}
+
+/// Default implementation of ConfigStorage using file system
+pub struct FileConfigStorage {
+    config_path: PathBuf,
+}
+
+impl FileConfigStorage {
+    pub fn new() -> Result<Self> {
+        Ok(Self::in_dir(&default_config_dir()?))
+    }
+
+    pub fn in_dir(dir: &Path) -> Self {
+        Self {
+            config_path: dir.join("config.json"),
+        }
+    }
+}
+
+/// Returns the directory holding the CLI's configuration files (`~/.skills`)
+pub fn default_config_dir() -> Result<PathBuf> {
+    let home_dir = dirs::home_dir().ok_or_else(|| anyhow!("Could not determine home directory"))?;
+    Ok(home_dir.join(".skills"))
+}
+
+impl ConfigStorage for FileConfigStorage {
+    fn load(&self) -> Result<Config> {
+        if !self.config_path.exists() {
+            return Ok(Config::default());
+        }
+
+        let content =
+            fs::read_to_string(&self.config_path).context("Failed to read config.json")?;
+
+        let config: Config =
+            serde_json::from_str(&content).context("Failed to parse config.json")?;
+
+        Ok(config)
+    }
+
+    fn save(&self, config: &Config) -> Result<()> {
+        if let Some(parent) = self.config_path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+
+        let json = serde_json::to_string_pretty(config).context("Failed to serialize config")?;
+
+        fs::write(&self.config_path, json).context("Failed to write config.json")?;
+
+        Ok(())
+    }
+}
+
+/// Service for managing skill aliases
+pub struct AliasService<C: ConfigStorage, U: GitHubUrlParser> {
+    storage: C,
+    url_parser: U,
+}
+
+impl<C: ConfigStorage, U: GitHubUrlParser> AliasService<C, U> {
+    pub fn new(storage: C, url_parser: U) -> Self {
+        Self {
+            storage,
+            url_parser,
+        }
+    }
+
+    pub fn add_alias(&self, name: &str, url: &str) -> Result<()> {
+        if name.starts_with("http") {
+            return Err(anyhow!("Alias name '{}' must not be a URL", name));
+        }
+
+        self.url_parser
+            .parse(url)
+            .context("Failed to parse GitHub URL")?;
+
+        let mut config = self.storage.load()?;
+        let previous = config.aliases.insert(name.to_string(), url.to_string());
+        self.storage.save(&config)?;
+
+        match previous {
+            Some(previous) if previous != url => {
+                println!("Updated alias '{}': {} (was {})", name, url, previous)
+            }
+            _ => println!("Successfully added alias '{}': {}", name, url),
+        }
+        Ok(())
+    }
+
+    pub fn remove_alias(&self, name: &str) -> Result<()> {
+        let mut config = self.storage.load()?;
+
+        if config.aliases.remove(name).is_none() {
+            return Err(anyhow!("Alias '{}' does not exist", name));
+        }
+
+        self.storage.save(&config)?;
+
+        println!("Removed alias '{}'", name);
+        Ok(())
+    }
+
+    pub fn list_aliases(&self) -> Result<()> {
+        let config = self.storage.load()?;
+
+        if config.aliases.is_empty() {
+            println!("No aliases configured");
+        } else {
+            for (name, url) in &config.aliases {
+                println!("{} -> {}", name, url);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn resolve(&self, name: &str) -> Result<Option<String>> {
+        let config = self.storage.load()?;
+        Ok(config.aliases.get(name).cloned())
+    }
+
+    /// Returns the URL to install directly for an install argument: the argument itself
+    /// when it is a URL, the aliased URL when it names an alias, the expanded URL for the
+    /// `owner/repo@branch:path` shorthand, or `None` when the skill has to be looked up in
+    /// the markets
+    pub fn resolve_source(&self, skill_or_url: &str) -> Result<Option<String>> {
+        if skill_or_url.starts_with("http") {
+            return Ok(Some(skill_or_url.to_string()));
+        }
+
+        let alias = self.resolve(skill_or_url)?;
+        if let Some(url) = &alias {
+            eprintln!("Resolved alias '{}' to {}", skill_or_url, url);
+            return Ok(alias);
+        }
+
+        Ok(expand_shorthand(skill_or_url))
+    }
+}
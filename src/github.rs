@@ -1,10 +1,18 @@
 use anyhow::{Context, Result, anyhow};
+use clap::ValueEnum;
+use rayon::prelude::*;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tempfile::TempDir;
 use walkdir::WalkDir;
 
-use crate::models::GitHubRepo;
+use crate::market::github_token;
+use crate::models::{GitHubRepo, GitTree};
+use crate::retry::RetryPolicy;
 
 /// Trait for parsing GitHub URLs
 pub trait GitHubUrlParser {
@@ -13,15 +21,55 @@ pub trait GitHubUrlParser {
 
 /// Trait for downloading content from GitHub
 pub trait GitHubDownloader {
-    fn download_folder(&self, repo: &GitHubRepo, target_dir: &Path, skill_name: &str)
+    /// Copies a fetched folder's contents into `dest_path`, merging with or replacing an
+    /// existing install; callers check `skips_existing` first
+    fn install_folder(&self, folder: &FetchedFolder, dest_path: &Path) -> Result<()>;
+
+    /// Whether the overwrite policy leaves an existing install at `dest_path` untouched
+    fn skips_existing(&self, dest_path: &Path) -> bool;
+
+    /// Repackages the skill folder as a tarball rooted at `skill_name` and writes it to `out`
+    fn write_tarball(&self, repo: &GitHubRepo, skill_name: &str, out: &mut dyn Write)
     -> Result<()>;
+
+    /// Downloads only the skill's SKILL.md
+    fn download_manifest(&self, repo: &GitHubRepo) -> Result<Vec<u8>>;
+
+    /// Downloads the repository folder into a temporary directory. Progress goes to stderr.
+    fn fetch_folder(&self, repo: &GitHubRepo) -> Result<FetchedFolder>;
+}
+
+/// A skill folder downloaded into a temporary directory, removed again on drop
+pub struct FetchedFolder {
+    /// The folder's path inside the temporary directory
+    pub path: PathBuf,
+    /// Branch the folder was fetched from, which differs from the requested one after
+    /// branch fallback
+    pub branch: String,
+    /// The folder's SKILL.md, read before `strip_components` and `only` could drop it
+    pub manifest: Option<Vec<u8>>,
+    _temp_dir: TempDir,
 }
 
-/// Trait for file system operations
-pub trait FileSystem {
+/// Name of the manifest file at the root of every skill folder
+pub const MANIFEST_FILE: &str = "SKILL.md";
+
+/// Trait for file system operations. Files may be written from several threads at once.
+pub trait FileSystem: Sync {
     fn copy_dir_all(&self, src: &Path, dst: &Path) -> Result<()>;
     fn create_dir_all(&self, path: &Path) -> Result<()>;
     fn write_file(&self, path: &Path, content: &[u8]) -> Result<()>;
+
+    /// Removes everything under `dst` that has no counterpart under `src`,
+    /// returning how many files and directories were removed
+    fn prune_dir(&self, src: &Path, dst: &Path) -> Result<usize>;
+
+    /// Clears (or restores) the owner's write permission on everything under `path`,
+    /// including `path` itself
+    fn set_read_only(&self, path: &Path, read_only: bool) -> Result<()>;
+
+    /// Deletes `path` and everything under it
+    fn remove_dir_all(&self, path: &Path) -> Result<()>;
 }
 
 /// Default implementation of GitHubUrlParser
@@ -66,6 +114,8 @@ impl GitHubUrlParser for DefaultGitHubUrlParser {
             repo: repo.to_string(),
             branch: branch.to_string(),
             path,
+            is_tag: false,
+            pinned: false,
         })
     }
 }
@@ -76,8 +126,13 @@ pub struct DefaultFileSystem;
 
 impl FileSystem for DefaultFileSystem {
     fn copy_dir_all(&self, src: &Path, dst: &Path) -> Result<()> {
+        let src = &long_path(src)?;
+        let dst = &long_path(dst)?;
         fs::create_dir_all(dst)?;
 
+        // Directories are created in walk order first so that the file copies, which run
+        // in parallel, always find their parent in place
+        let mut files = Vec::new();
         for entry in WalkDir::new(src).min_depth(1) {
             let entry = entry?;
             let path = entry.path();
@@ -90,94 +145,1016 @@ impl FileSystem for DefaultFileSystem {
             if entry.file_type().is_dir() {
                 fs::create_dir_all(&dest_path)?;
             } else {
-                if let Some(parent) = dest_path.parent() {
-                    fs::create_dir_all(parent)?;
-                }
-                fs::copy(path, &dest_path)?;
+                files.push((path.to_path_buf(), dest_path));
             }
         }
 
-        Ok(())
+        files.par_iter().try_for_each(|(path, dest_path)| {
+            fs::copy(path, dest_path)
+                .with_context(|| format!("Failed to copy {}", path.display()))
+                .map(|_| ())
+        })
     }
 
     fn create_dir_all(&self, path: &Path) -> Result<()> {
-        fs::create_dir_all(path).context("Failed to create directory")
+        fs::create_dir_all(long_path(path)?).context("Failed to create directory")
     }
 
     fn write_file(&self, path: &Path, content: &[u8]) -> Result<()> {
         fs::write(path, content).context("Failed to write file")
     }
+
+    fn prune_dir(&self, src: &Path, dst: &Path) -> Result<usize> {
+        let mut removed = 0;
+
+        // Children come before their parents, so a removed directory is never revisited
+        for entry in WalkDir::new(dst).min_depth(1).contents_first(true) {
+            let entry = entry?;
+            let path = entry.path();
+
+            if !path.exists() {
+                continue;
+            }
+
+            let relative_path = path
+                .strip_prefix(dst)
+                .context("Failed to get relative path")?;
+            if src.join(relative_path).exists() {
+                continue;
+            }
+
+            if entry.file_type().is_dir() {
+                fs::remove_dir_all(path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+            } else {
+                fs::remove_file(path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+            }
+
+            println!("Removed: {}", relative_path.display());
+            removed += 1;
+        }
+
+        Ok(removed)
+    }
+
+    fn set_read_only(&self, path: &Path, read_only: bool) -> Result<()> {
+        for entry in WalkDir::new(path) {
+            let entry = entry?;
+            // Permissions set through a symlink would land on its target, possibly
+            // outside the skill. Windows ignores the read-only attribute on directories.
+            if entry.file_type().is_symlink() || (cfg!(windows) && entry.file_type().is_dir()) {
+                continue;
+            }
+
+            let mut permissions = entry.metadata()?.permissions();
+            set_writable(&mut permissions, !read_only);
+            fs::set_permissions(entry.path(), permissions).with_context(|| {
+                format!("Failed to change permissions of {}", entry.path().display())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<()> {
+        fs::remove_dir_all(long_path(path)?)
+            .with_context(|| format!("Failed to remove {}", path.display()))
+    }
+}
+
+/// Sets or clears the owner's write bit, leaving the other permission bits alone
+#[cfg(unix)]
+fn set_writable(permissions: &mut fs::Permissions, writable: bool) {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = permissions.mode();
+    permissions.set_mode(if writable {
+        mode | 0o200
+    } else {
+        mode & !0o222
+    });
+}
+
+#[cfg(not(unix))]
+fn set_writable(permissions: &mut fs::Permissions, writable: bool) {
+    permissions.set_readonly(!writable);
+}
+
+/// Converts a path to its extended-length form (`\\?\`) so that deeply nested skills are
+/// not rejected by the MAX_PATH limit, neither where they are extracted nor where they
+/// are copied to
+#[cfg(windows)]
+fn long_path(path: &Path) -> Result<PathBuf> {
+    let absolute = std::path::absolute(path).context("Failed to resolve absolute path")?;
+    let raw = absolute.as_os_str().to_string_lossy();
+
+    if raw.starts_with(r"\\?\") {
+        Ok(absolute)
+    } else if let Some(unc) = raw.strip_prefix(r"\\") {
+        Ok(PathBuf::from(format!(r"\\?\UNC\{}", unc)))
+    } else {
+        Ok(PathBuf::from(format!(r"\\?\{}", raw)))
+    }
+}
+
+#[cfg(not(windows))]
+fn long_path(path: &Path) -> Result<PathBuf> {
+    Ok(path.to_path_buf())
 }
 
 /// Default implementation of GitHubDownloader
 pub struct DefaultGitHubDownloader<F: FileSystem> {
     file_system: F,
+    options: DownloadOptions,
+}
+
+/// What an install does when its destination already holds files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OverwritePolicy {
+    /// Copy over the existing files, keeping files that are not upstream
+    #[default]
+    Merge,
+    /// Delete the existing folder, then copy
+    Replace,
+    /// Leave the existing folder untouched, without fetching the archive
+    Skip,
+}
+
+/// Options controlling how archives are fetched and copied into place
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// Branches tried, in order, when the requested branch does not exist
+    pub branch_fallback: Vec<String>,
+    /// Remove files in the destination that are not present upstream
+    pub prune: bool,
+    /// Files larger than this many bytes are reported with a warning
+    pub large_file_limit: u64,
+    /// Leave files over `large_file_limit` out of the install instead of only warning
+    pub skip_large: bool,
+    /// Fetch only the skill's files through the git trees API instead of the whole
+    /// repository archive
+    pub tree_api: bool,
+    /// Make the installed files read-only
+    pub read_only: bool,
+    /// Retrying of transient HTTP failures
+    pub retry: RetryPolicy,
+    /// Extra headers sent with every download request
+    pub headers: HeaderMap,
+    /// Directory the download and extraction temp directories are created in, instead of
+    /// the system temp directory
+    pub temp_dir: Option<PathBuf>,
+    /// Leading path components dropped from every file under the skill folder, like
+    /// tar's `--strip-components`
+    pub strip_components: usize,
+    /// Handling of a destination that already holds files
+    pub overwrite: OverwritePolicy,
+    /// Take the archive's single top-level folder as the repository root; when off, only
+    /// the `{repo}-{branch}` folder GitHub normally creates is used
+    pub subfolder_detect: bool,
+    /// Install only these files and directories, relative to the skill folder
+    pub only: Vec<PathBuf>,
+}
+
+/// Size above which a single skill file is reported as large (25 MiB)
+pub const DEFAULT_LARGE_FILE_LIMIT: u64 = 25 * 1024 * 1024;
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            branch_fallback: default_branch_fallback(),
+            prune: false,
+            large_file_limit: DEFAULT_LARGE_FILE_LIMIT,
+            skip_large: false,
+            tree_api: false,
+            read_only: false,
+            retry: RetryPolicy::default(),
+            headers: HeaderMap::new(),
+            temp_dir: None,
+            strip_components: 0,
+            overwrite: OverwritePolicy::Merge,
+            subfolder_detect: true,
+            only: Vec::new(),
+        }
+    }
 }
 
 impl<F: FileSystem> DefaultGitHubDownloader<F> {
     pub fn new(file_system: F) -> Self {
-        Self { file_system }
+        Self {
+            file_system,
+            options: DownloadOptions::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: DownloadOptions) -> Self {
+        self.options = options;
+        self
     }
 }
 
-impl<F: FileSystem> GitHubDownloader for DefaultGitHubDownloader<F> {
-    fn download_folder(
+pub fn default_branch_fallback() -> Vec<String> {
+    vec!["main".to_string(), "master".to_string()]
+}
+
+impl<F: FileSystem> DefaultGitHubDownloader<F> {
+    /// Downloads the repository archive, retrying against the fallback branches when
+    /// the requested branch returns 404. Returns the archive bytes and the branch used.
+    fn download_archive(&self, repo: &GitHubRepo) -> Result<(Vec<u8>, String)> {
+        let branches = self.candidate_branches(repo);
+        let client = self.http_client()?;
+
+        for (i, branch) in branches.iter().enumerate() {
+            let zip_url = archive_url(repo, branch);
+            if i > 0 {
+                eprintln!("Retrying with branch '{}': {}", branch, zip_url);
+            }
+
+            let Some(bytes) = request_archive(&client, &zip_url, &self.options.retry)? else {
+                eprintln!("Warning: Branch '{}' not found", branch);
+                continue;
+            };
+
+            if i > 0 {
+                eprintln!("Using branch '{}' instead of '{}'", branch, repo.branch);
+            }
+
+            return Ok((bytes, branch.to_string()));
+        }
+
+        Err(anyhow!(
+            "Failed to download: none of the branches {} exist in {}/{}",
+            branches.join(", "),
+            repo.owner,
+            repo.repo
+        ))
+    }
+
+    /// The requested branch followed by the fallback branches not already tried
+    fn candidate_branches<'a>(&'a self, repo: &'a GitHubRepo) -> Vec<&'a str> {
+        let mut branches = vec![repo.branch.as_str()];
+        // A missing tag or named ref is an error, not a reason to install whatever another
+        // branch holds
+        if repo.is_tag || repo.pinned {
+            return branches;
+        }
+        for branch in &self.options.branch_fallback {
+            if !branches.contains(&branch.as_str()) {
+                branches.push(branch);
+            }
+        }
+        branches
+    }
+
+    /// URL the skill is fetched from, for progress messages
+    fn source_url(&self, repo: &GitHubRepo) -> String {
+        if self.options.tree_api {
+            tree_url(repo, &repo.branch)
+        } else {
+            archive_url(repo, &repo.branch)
+        }
+    }
+
+    /// Creates a temp directory, removed when dropped, in the configured location
+    fn temp_dir(&self) -> Result<TempDir> {
+        match &self.options.temp_dir {
+            Some(parent) => {
+                fs::create_dir_all(parent).with_context(|| {
+                    format!("Failed to create temp directory in {}", parent.display())
+                })?;
+                TempDir::new_in(parent).with_context(|| {
+                    format!("Failed to create temp directory in {}", parent.display())
+                })
+            }
+            None => TempDir::new().context("Failed to create temp directory"),
+        }
+    }
+
+    /// HTTP client sending the configured extra headers with every request
+    fn http_client(&self) -> Result<reqwest::blocking::Client> {
+        reqwest::blocking::Client::builder()
+            .user_agent("skills-cli")
+            .default_headers(self.options.headers.clone())
+            .build()
+            .context("Failed to create HTTP client")
+    }
+
+    /// Lists the repository through the git trees API, trying the fallback branches like
+    /// `download_archive`. Returns the tree and the branch used.
+    fn download_tree(
         &self,
+        client: &reqwest::blocking::Client,
         repo: &GitHubRepo,
-        target_dir: &Path,
-        skill_name: &str,
-    ) -> Result<()> {
-        let zip_url = format!(
-            "https://github.com/{}/{}/archive/refs/heads/{}.zip",
-            repo.owner, repo.repo, repo.branch
-        );
+    ) -> Result<(GitTree, String)> {
+        let branches = self.candidate_branches(repo);
 
-        println!("Downloading from GitHub: {}", zip_url);
+        for (i, branch) in branches.iter().enumerate() {
+            let url = tree_url(repo, branch);
+            if i > 0 {
+                eprintln!("Retrying with branch '{}': {}", branch, url);
+            }
 
-        let response = reqwest::blocking::get(&zip_url).context("Failed to download repository")?;
+            let response = self
+                .options
+                .retry
+                .send(|| authorized_get(client, &url))
+                .context("Failed to list repository files")?;
 
-        if !response.status().is_success() {
-            return Err(anyhow!("Failed to download: HTTP {}", response.status()));
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                eprintln!("Warning: Branch '{}' not found", branch);
+                continue;
+            }
+
+            if !response.status().is_success() {
+                return Err(anyhow!(
+                    "Failed to list repository files: HTTP {}",
+                    response.status()
+                ));
+            }
+
+            if i > 0 {
+                eprintln!("Using branch '{}' instead of '{}'", branch, repo.branch);
+            }
+
+            let tree: GitTree = response
+                .json()
+                .context("Failed to parse git trees API response")?;
+            return Ok((tree, branch.to_string()));
         }
 
-        let temp_dir = TempDir::new().context("Failed to create temp directory")?;
-        let zip_path = temp_dir.path().join("repo.zip");
+        Err(anyhow!(
+            "Failed to download: none of the branches {} exist in {}/{}",
+            branches.join(", "),
+            repo.owner,
+            repo.repo
+        ))
+    }
 
-        let bytes = response.bytes().context("Failed to read response bytes")?;
-        self.file_system.write_file(&zip_path, &bytes)?;
+    /// Downloads only the files under the skill folder, concurrently, into a temp
+    /// directory. Returns the temp directory together with the folder holding the files.
+    fn fetch_tree_source(&self, repo: &GitHubRepo) -> Result<(TempDir, PathBuf, String)> {
+        let client = self.http_client()?;
+        let (tree, branch) = self.download_tree(&client, repo)?;
 
-        let file = fs::File::open(&zip_path).context("Failed to open zip file")?;
-        let mut archive = zip::ZipArchive::new(file).context("Failed to read zip archive")?;
+        if tree.truncated {
+            eprintln!(
+                "Warning: {}/{} is too large for the git trees API to list completely; some files may be missing",
+                repo.owner, repo.repo
+            );
+        }
+
+        let prefix = if repo.path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", repo.path.trim_end_matches('/'))
+        };
+        let files = tree
+            .tree
+            .iter()
+            .filter(|entry| entry.item_type == "blob")
+            .filter_map(|entry| entry.path.strip_prefix(prefix.as_str()))
+            .collect::<Vec<_>>();
 
-        let extract_dir = temp_dir.path().join("extracted");
+        if files.is_empty() {
+            return Err(anyhow!("Path '{}' not found in repository", repo.path));
+        }
+
+        let temp_dir = self.temp_dir()?;
+        let source_path = long_path(&temp_dir.path().join("tree"))?;
+        let at_branch = GitHubRepo {
+            branch: branch.clone(),
+            ..repo.clone()
+        };
+
+        files.par_iter().try_for_each(|file| {
+            let file_url = raw_file_url(&at_branch, file);
+            let response = self
+                .options
+                .retry
+                .send(|| authorized_get(&client, &file_url))
+                .with_context(|| format!("Failed to download {}", file))?;
+            if !response.status().is_success() {
+                return Err(anyhow!(
+                    "Failed to download {}: HTTP {}",
+                    file,
+                    response.status()
+                ));
+            }
+            let bytes = response
+                .bytes()
+                .with_context(|| format!("Failed to download {}", file))?;
+
+            let dest = source_path.join(file);
+            if let Some(parent) = dest.parent() {
+                self.file_system.create_dir_all(parent)?;
+            }
+            self.file_system.write_file(&dest, &bytes)
+        })?;
+
+        self.check_large_files(&source_path)?;
+
+        Ok((temp_dir, source_path, branch))
+    }
+
+    /// Downloads the requested folder, dropping `strip_components` path components and
+    /// leaving out everything not listed in `only`
+    fn fetch_source(&self, repo: &GitHubRepo) -> Result<FetchedFolder> {
+        let (temp_dir, source_path, branch) = if self.options.tree_api {
+            self.fetch_tree_source(repo)?
+        } else {
+            self.fetch_archive_source(repo)?
+        };
+
+        let manifest = match fs::read(source_path.join(MANIFEST_FILE)) {
+            Ok(manifest) => Some(manifest),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).context("Failed to read SKILL.md"),
+        };
+
+        let source_path = if self.options.strip_components == 0 {
+            source_path
+        } else {
+            let stripped = long_path(&temp_dir.path().join("stripped"))?;
+            strip_components(&source_path, &stripped, self.options.strip_components)?;
+            stripped
+        };
+
+        let path = if self.options.only.is_empty() {
+            source_path
+        } else {
+            let selected = long_path(&temp_dir.path().join("selected"))?;
+            select_paths(&source_path, &selected, &self.options.only)?;
+            selected
+        };
+
+        Ok(FetchedFolder {
+            path,
+            branch,
+            manifest,
+            _temp_dir: temp_dir,
+        })
+    }
+
+    /// Downloads and extracts the repository archive, returning the temp directory
+    /// holding the extraction together with the path of the requested folder inside it
+    fn fetch_archive_source(&self, repo: &GitHubRepo) -> Result<(TempDir, PathBuf, String)> {
+        let (bytes, branch) = self.download_archive(repo)?;
+
+        let temp_dir = self.temp_dir()?;
+        let archive_path = temp_dir.path().join("repo.archive");
+
+        self.file_system.write_file(&archive_path, &bytes)?;
+
+        // Everything found under the extraction root inherits its extended-length form
+        let extract_dir = long_path(&temp_dir.path().join("extracted"))?;
         self.file_system.create_dir_all(&extract_dir)?;
 
-        archive
-            .extract(&extract_dir)
-            .context("Failed to extract archive")?;
+        extract_archive(&archive_path, &extract_dir)?;
 
+        let archive_root = find_archive_root(
+            &extract_dir,
+            &repo.repo,
+            &branch,
+            self.options.subfolder_detect,
+        )?;
         let source_path = if repo.path.is_empty() {
-            extract_dir.join(format!("{}-{}", repo.repo, repo.branch))
+            archive_root
         } else {
-            extract_dir
-                .join(format!("{}-{}", repo.repo, repo.branch))
-                .join(&repo.path)
+            archive_root.join(&repo.path)
         };
 
         if !source_path.exists() {
             return Err(anyhow!("Path '{}' not found in repository", repo.path));
         }
 
-        let dest_path = target_dir.join(skill_name);
-        self.file_system.create_dir_all(&dest_path)?;
+        self.check_large_files(&source_path)?;
+
+        Ok((temp_dir, source_path, branch))
+    }
+
+    /// Warns about files over the size limit, deleting them from the extracted source
+    /// when large files are skipped so they never reach the destination
+    fn check_large_files(&self, source_path: &Path) -> Result<()> {
+        for entry in WalkDir::new(source_path).min_depth(1) {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let size = entry.metadata()?.len();
+            if size <= self.options.large_file_limit {
+                continue;
+            }
+
+            let relative = entry
+                .path()
+                .strip_prefix(source_path)
+                .unwrap_or(entry.path());
+            let size_mib = size as f64 / (1024.0 * 1024.0);
+            if self.options.skip_large {
+                fs::remove_file(entry.path())
+                    .with_context(|| format!("Failed to skip {}", relative.display()))?;
+                eprintln!(
+                    "Warning: Skipping large file {} ({:.1} MiB)",
+                    relative.display(),
+                    size_mib
+                );
+            } else {
+                eprintln!(
+                    "Warning: Large file {} ({:.1} MiB); pass --skip-large to leave it out",
+                    relative.display(),
+                    size_mib
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<F: FileSystem> GitHubDownloader for DefaultGitHubDownloader<F> {
+    fn install_folder(&self, folder: &FetchedFolder, dest_path: &Path) -> Result<()> {
+        let source_path = &folder.path;
+        let occupied = is_occupied(dest_path);
+
+        // A previous read-only install has to be writable again before it is overwritten
+        if dest_path.exists() {
+            self.file_system.set_read_only(dest_path, false)?;
+        }
+
+        // Only removed once the download succeeded, so a failure keeps the old install
+        if occupied && self.options.overwrite == OverwritePolicy::Replace {
+            println!("Removing existing files in: {}", dest_path.display());
+            self.file_system.remove_dir_all(dest_path)?;
+        }
+
+        self.file_system.create_dir_all(dest_path)?;
 
         println!("Copying files to: {}", dest_path.display());
-        self.file_system.copy_dir_all(&source_path, &dest_path)?;
+        self.file_system.copy_dir_all(source_path, dest_path)?;
+
+        if self.options.prune {
+            let removed = self.file_system.prune_dir(source_path, dest_path)?;
+            println!("Pruned {} path(s) no longer present upstream", removed);
+        }
+
+        if self.options.read_only {
+            self.file_system.set_read_only(dest_path, true)?;
+            println!("Marked installed files read-only");
+        }
 
         println!("Successfully installed skill to: {}", dest_path.display());
 
         Ok(())
     }
+
+    fn skips_existing(&self, dest_path: &Path) -> bool {
+        self.options.overwrite == OverwritePolicy::Skip && is_occupied(dest_path)
+    }
+
+    fn write_tarball(
+        &self,
+        repo: &GitHubRepo,
+        skill_name: &str,
+        out: &mut dyn Write,
+    ) -> Result<()> {
+        let zip_url = self.source_url(repo);
+
+        // stdout carries the tarball, so progress goes to stderr
+        eprintln!("Downloading from GitHub: {}", zip_url);
+
+        let folder = self.fetch_source(repo)?;
+
+        let mut builder = tar::Builder::new(out);
+        builder
+            .append_dir_all(skill_name, &folder.path)
+            .context("Failed to build tarball")?;
+        builder
+            .into_inner()
+            .context("Failed to finish tarball")?
+            .flush()
+            .context("Failed to write tarball")?;
+
+        eprintln!("Wrote skill '{}' as tarball to stdout", skill_name);
+
+        Ok(())
+    }
+
+    fn fetch_folder(&self, repo: &GitHubRepo) -> Result<FetchedFolder> {
+        eprintln!("Downloading from GitHub: {}", self.source_url(repo));
+        self.fetch_source(repo)
+    }
+
+    fn download_manifest(&self, repo: &GitHubRepo) -> Result<Vec<u8>> {
+        let manifest_url = raw_file_url(repo, MANIFEST_FILE);
+
+        eprintln!("Downloading from GitHub: {}", manifest_url);
+
+        let client = self.http_client()?;
+        let response = self
+            .options
+            .retry
+            .send(|| authorized_get(&client, &manifest_url))
+            .context("Failed to download SKILL.md")?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(anyhow!(
+                "No {} found at '{}' in {}/{}",
+                MANIFEST_FILE,
+                repo.path,
+                repo.owner,
+                repo.repo
+            ));
+        }
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to download: HTTP {}", response.status()));
+        }
+
+        let bytes = response.bytes().context("Failed to read response bytes")?;
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Whether `dest_path` is a directory with anything in it
+fn is_occupied(dest_path: &Path) -> bool {
+    fs::read_dir(dest_path).is_ok_and(|mut entries| entries.next().is_some())
+}
+
+/// Returns the raw.githubusercontent.com URL of `file` inside the repository path, with
+/// every path segment percent-encoded so names holding spaces, `#` or `?` still resolve
+pub fn raw_file_url(repo: &GitHubRepo, file: &str) -> String {
+    let base = format!(
+        "https://raw.githubusercontent.com/{}/{}/{}",
+        repo.owner,
+        repo.repo,
+        encode_path(&repo.branch)
+    );
+
+    if repo.path.is_empty() {
+        format!("{}/{}", base, encode_path(file))
+    } else {
+        format!("{}/{}/{}", base, encode_path(&repo.path), encode_path(file))
+    }
+}
+
+/// Percent-encodes each `/`-separated segment of `path`, leaving the separators alone
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            byte => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Expands the `owner/repo@branch[:path]` and `owner/repo:path` shorthands into a GitHub
+/// tree URL, or returns `None` when `source` is not in that form. The branch defaults to
+/// `main`. A bare `owner/repo` is left to `bare_repo`, since its skill has to be looked up.
+pub fn expand_shorthand(source: &str) -> Option<String> {
+    if bare_repo(source).is_some() {
+        return None;
+    }
+
+    let (location, path) = match source.split_once(':') {
+        Some((location, path)) => (location, path.trim_matches('/')),
+        None => (source, ""),
+    };
+    let (repo_path, branch) = match location.split_once('@') {
+        Some((repo_path, branch)) => (repo_path, branch),
+        None => (location, "main"),
+    };
+
+    let (owner, repo) = repo_path.split_once('/')?;
+    let valid = |part: &str| !part.is_empty() && !part.contains(['/', '@', ' ']);
+    if !valid(owner) || !valid(repo) || branch.is_empty() {
+        return None;
+    }
+
+    let url = format!("https://github.com/{}/{}/tree/{}", owner, repo, branch);
+    if path.is_empty() {
+        Some(url)
+    } else {
+        Some(format!("{}/{}", url, path))
+    }
+}
+
+/// Returns `owner/repo` when `source` is exactly that, with no branch or path
+pub fn bare_repo(source: &str) -> Option<&str> {
+    let (owner, repo) = source.split_once('/')?;
+    let valid = |part: &str| !part.is_empty() && !part.contains(['/', '@', ':', ' ']);
+    (valid(owner) && valid(repo)).then_some(source)
+}
+
+/// Parses `Key: Value` header arguments, rejecting malformed names and values
+pub fn parse_headers(headers: &[String]) -> Result<HeaderMap> {
+    let mut map = HeaderMap::new();
+    for header in headers {
+        let (name, value) = header
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Invalid header '{}': expected 'Key: Value'", header))?;
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .with_context(|| format!("Invalid header name in '{}'", header))?;
+        let value = HeaderValue::from_str(value.trim())
+            .with_context(|| format!("Invalid header value in '{}'", header))?;
+        map.append(name, value);
+    }
+    Ok(map)
+}
+
+/// Returns the git trees API URL listing every file in the repository at `branch`
+fn tree_url(repo: &GitHubRepo, branch: &str) -> String {
+    format!(
+        "https://api.github.com/repos/{}/{}/git/trees/{}?recursive=1",
+        repo.owner, repo.repo, branch
+    )
+}
+
+/// Builds a GET request, authenticated when a GitHub token is set so private repositories
+/// and higher rate limits are available
+fn authorized_get(
+    client: &reqwest::blocking::Client,
+    url: &str,
+) -> reqwest::blocking::RequestBuilder {
+    let request = client.get(url);
+    match github_token() {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+/// Moves the listed files and directories from `src` to the same relative place under
+/// `dst`, failing before moving anything when one of them is missing or leaves `src`
+fn select_paths(src: &Path, dst: &Path, paths: &[PathBuf]) -> Result<()> {
+    for path in paths {
+        let inside = path
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)));
+        if !inside || path.as_os_str().is_empty() {
+            return Err(anyhow!(
+                "--only path '{}' must be relative to the skill folder",
+                path.display()
+            ));
+        }
+        if !src.join(path).exists() {
+            return Err(anyhow!(
+                "--only path '{}' does not exist in the skill folder",
+                path.display()
+            ));
+        }
+    }
+
+    fs::create_dir_all(dst)?;
+    for path in paths {
+        // Already moved along with a listed parent directory
+        if paths
+            .iter()
+            .any(|other| other != path && path.starts_with(other))
+        {
+            continue;
+        }
+        let dest = dst.join(path);
+        if dest.exists() {
+            continue;
+        }
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(src.join(path), &dest)
+            .with_context(|| format!("Failed to select {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Leading bytes of a zip archive
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Leading bytes of a gzip stream
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// Extracts a zip or gzipped tar archive into `dest`, telling the two apart by their
+/// leading bytes rather than the file name
+fn extract_archive(archive_path: &Path, dest: &Path) -> Result<()> {
+    let mut file = fs::File::open(archive_path).context("Failed to open archive")?;
+    let mut magic = [0u8; 4];
+    let read = file.read(&mut magic).context("Failed to read archive")?;
+    file.rewind().context("Failed to read archive")?;
+
+    if magic[..read].starts_with(ZIP_MAGIC) {
+        zip::ZipArchive::new(file)
+            .context("Failed to read zip archive")?
+            .extract(dest)
+            .context("Failed to extract archive")
+    } else if magic[..read].starts_with(GZIP_MAGIC) {
+        tar::Archive::new(flate2::read::GzDecoder::new(file))
+            .unpack(dest)
+            .context("Failed to extract tar.gz archive")
+    } else {
+        Err(anyhow!(
+            "Downloaded file is neither a zip nor a tar.gz archive"
+        ))
+    }
+}
+
+/// Moves every file under `src` to `dst` without its first `n` path components. Files
+/// nested no deeper than `n` directories are dropped, as with tar. Fails before moving
+/// anything when two files would end up at the same path, or a file where a directory is
+/// needed.
+fn strip_components(src: &Path, dst: &Path, n: usize) -> Result<()> {
+    let mut moves = BTreeMap::new();
+    for entry in WalkDir::new(src).min_depth(n + 1) {
+        let entry = entry?;
+        if entry.file_type().is_dir() {
+            continue;
+        }
+
+        let relative = entry
+            .path()
+            .strip_prefix(src)
+            .context("Failed to get relative path")?;
+        let stripped: PathBuf = relative.components().skip(n).collect();
+        if let Some(previous) = moves.insert(stripped.clone(), relative.to_path_buf()) {
+            return Err(anyhow!(
+                "--strip-components {} would overwrite {} with {}",
+                n,
+                previous.display(),
+                relative.display()
+            ));
+        }
+    }
+
+    for (stripped, relative) in &moves {
+        if let Some(clash) = stripped
+            .ancestors()
+            .skip(1)
+            .find(|a| moves.contains_key(*a))
+        {
+            return Err(anyhow!(
+                "--strip-components {} would turn {} into a directory for {}",
+                n,
+                moves[clash].display(),
+                relative.display()
+            ));
+        }
+    }
+
+    for (stripped, relative) in &moves {
+        let dest = dst.join(stripped);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(src.join(relative), &dest)
+            .with_context(|| format!("Failed to move {}", relative.display()))?;
+    }
+    fs::create_dir_all(dst)?;
+
+    Ok(())
+}
+
+/// Returns the folder GitHub placed the repository contents in. This is normally
+/// `{repo}-{branch}` with slashes in the branch turned into hyphens, but forks, renamed
+/// repositories and tags can differ, so the single top-level directory is preferred.
+/// Stray top-level files are ignored, since they never hold the repository contents.
+/// Without `detect`, only the `{repo}-{branch}` folder is accepted.
+fn find_archive_root(
+    extract_dir: &Path,
+    repo: &str,
+    branch: &str,
+    detect: bool,
+) -> Result<PathBuf> {
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(extract_dir).context("Failed to read extracted archive")? {
+        let entry = entry.context("Failed to read extracted archive")?;
+        if entry.file_type()?.is_dir() {
+            dirs.push(entry.path());
+        }
+    }
+
+    if detect && let [dir] = dirs.as_slice() {
+        return Ok(dir.clone());
+    }
+
+    let expected = extract_dir.join(format!("{}-{}", repo, branch.replace('/', "-")));
+    if dirs.contains(&expected) {
+        return Ok(expected);
+    }
+
+    Err(anyhow!(
+        "Could not find the repository folder in the archive (expected {})",
+        expected
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    ))
+}
+
+/// Number of times a 202 "archive is being prepared" response is retried
+const ARCHIVE_PENDING_RETRIES: u32 = 5;
+
+/// Delay between polls while GitHub prepares an archive
+const ARCHIVE_PENDING_DELAY: Duration = Duration::from_secs(2);
+
+/// Downloads an archive, returning `None` on 404. GitHub answers 202 with an empty body
+/// while it is still generating the zip, so those responses are polled until the
+/// archive arrives or the retries run out. A truncated download is retried like any
+/// other transient failure.
+fn request_archive(
+    client: &reqwest::blocking::Client,
+    zip_url: &str,
+    retry: &RetryPolicy,
+) -> Result<Option<Vec<u8>>> {
+    let mut pending = 0;
+    let mut attempt = 1;
+    loop {
+        let response = retry
+            .send(|| authorized_get(client, zip_url))
+            .context("Failed to download repository")?;
+        let status = response.status();
+
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !status.is_success() {
+            return Err(anyhow!("Failed to download: HTTP {}", status));
+        }
+
+        let expected_len = response.content_length();
+        let checked = response
+            .bytes()
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| check_archive(&bytes, expected_len).map(|_| bytes));
+
+        let bytes = match checked {
+            Ok(bytes) if status == reqwest::StatusCode::ACCEPTED || bytes.is_empty() => {
+                pending += 1;
+                if pending > ARCHIVE_PENDING_RETRIES {
+                    return Err(anyhow!(
+                        "Failed to download: GitHub did not finish preparing the archive after {} retries",
+                        ARCHIVE_PENDING_RETRIES
+                    ));
+                }
+                eprintln!(
+                    "GitHub is still preparing the archive, retrying in {}s...",
+                    ARCHIVE_PENDING_DELAY.as_secs()
+                );
+                std::thread::sleep(ARCHIVE_PENDING_DELAY);
+                continue;
+            }
+            Ok(bytes) => bytes,
+            Err(reason) if attempt < retry.attempts => {
+                let delay = retry.backoff(attempt);
+                eprintln!(
+                    "Warning: Download incomplete ({}), retrying in {:.1}s (attempt {} of {})",
+                    reason,
+                    delay.as_secs_f64(),
+                    attempt + 1,
+                    retry.attempts
+                );
+                std::thread::sleep(delay);
+                attempt += 1;
+                continue;
+            }
+            Err(reason) => {
+                return Err(anyhow!(
+                    "Failed to download: download incomplete ({}) after {} attempt(s)",
+                    reason,
+                    attempt
+                ));
+            }
+        };
+
+        return Ok(Some(bytes.to_vec()));
+    }
+}
+
+/// Checks that a downloaded archive is complete: as long as the server announced, and
+/// for a zip, ending in a readable central directory. An empty body is accepted here,
+/// since GitHub sends one while the archive is still being prepared.
+fn check_archive(bytes: &[u8], expected_len: Option<u64>) -> std::result::Result<(), String> {
+    if let Some(expected) = expected_len
+        && bytes.len() as u64 != expected
+    {
+        return Err(format!("got {} of {} bytes", bytes.len(), expected));
+    }
+    if !bytes.starts_with(ZIP_MAGIC) {
+        return Ok(());
+    }
+    zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .map(|_| ())
+        .map_err(|e| format!("invalid zip: {}", e))
+}
+
+fn archive_url(repo: &GitHubRepo, branch: &str) -> String {
+    let kind = if repo.is_tag { "tags" } else { "heads" };
+    format!(
+        "https://github.com/{}/{}/archive/refs/{}/{}.zip",
+        repo.owner, repo.repo, kind, branch
+    )
 }
 
 pub fn extract_skill_name(path: &str) -> Result<String> {
@@ -188,3 +1165,24 @@ pub fn extract_skill_name(path: &str) -> Result<String> {
         .ok_or_else(|| anyhow!("Could not extract skill name from path"))?;
     Ok(name.to_string())
 }
+
+#[cfg(all(test, windows))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_trees_deeper_than_max_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let src = temp_dir.path().join("src");
+        let relative: PathBuf = (0..30).map(|i| format!("nested-folder-{:02}", i)).collect();
+        let deep_dir = long_path(&src.join(&relative)).unwrap();
+        fs::create_dir_all(&deep_dir).unwrap();
+        fs::write(deep_dir.join(MANIFEST_FILE), "deep").unwrap();
+
+        let dst = temp_dir.path().join("dst");
+        DefaultFileSystem.copy_dir_all(&src, &dst).unwrap();
+
+        let copied = long_path(&dst.join(&relative).join(MANIFEST_FILE)).unwrap();
+        assert_eq!(fs::read_to_string(copied).unwrap(), "deep");
+    }
+}
@@ -1,20 +1,218 @@
 use anyhow::{Context, Result, anyhow};
-use std::path::PathBuf;
+use clap::ValueEnum;
+use globset::Glob;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use similar::TextDiff;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
 
-use crate::github::{GitHubDownloader, GitHubUrlParser, extract_skill_name};
+use crate::config::{
+    AliasService, ConfigStorage, PROJECT_CONFIG_FILE, ProjectConfig, load_project_config,
+};
+use crate::github::{
+    FileSystem, GitHubDownloader, GitHubUrlParser, MANIFEST_FILE, bare_repo, extract_skill_name,
+};
+use crate::manifest;
 use crate::market::{GitHubApiClient, MarketStorage};
-use crate::models::SkillMatch;
-use crate::skill_finder::{SkillFinder, UserInteraction};
+use crate::models::{ResolvedSkill, SkillMatch};
+use crate::skill_finder::{NameLookup, SkillFinder, UserInteraction};
 
 /// Trait for target type abstraction
 pub trait Target {
     fn as_str(&self) -> &'static str;
+
+    /// Folder under the project directory that holds the tool's `skills` directory
+    fn local_folder(&self) -> &'static str;
+
+    /// Folder under the home directory that holds the tool's `skills` directory
+    fn global_folder(&self) -> &'static str;
+}
+
+/// Tools that skills can be installed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetType {
+    Codex,
+    Copilot,
+    Claude,
+    Cursor,
+}
+
+impl Target for TargetType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TargetType::Codex => "codex",
+            TargetType::Copilot => "copilot",
+            TargetType::Claude => "claude",
+            TargetType::Cursor => "cursor",
+        }
+    }
+
+    fn local_folder(&self) -> &'static str {
+        match self {
+            TargetType::Codex => ".codex",
+            TargetType::Copilot => ".github",
+            TargetType::Claude => ".claude",
+            TargetType::Cursor => ".cursor",
+        }
+    }
+
+    fn global_folder(&self) -> &'static str {
+        match self {
+            TargetType::Codex => ".codex",
+            // Copilot reads personal skills from ~/.copilot, not ~/.github
+            TargetType::Copilot => ".copilot",
+            TargetType::Claude => ".claude",
+            TargetType::Cursor => ".cursor",
+        }
+    }
+}
+
+/// Ref that installs a repository's newest release instead of a branch
+pub const LATEST_REF: &str = "latest";
+
+/// Options applied when a skill folder is installed
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// Print the installed file tree after copying
+    pub print_tree: bool,
+    /// Limit how many levels of the tree are printed
+    pub max_depth: Option<usize>,
+    /// Install into `<name>@<ref>` so several versions can sit side by side
+    pub versioned: bool,
+    /// Install skills that declare a newer `min_cli_version`, with a warning
+    pub force: bool,
+    /// Print a SHA-256 fingerprint of the installed files
+    pub checksum: bool,
+    /// Accept URLs pointing at a repository root, installing the whole repository
+    pub allow_repo_root: bool,
+    /// Keep an `InstallRecord` of every install attempt
+    pub record: bool,
+    /// Pick this match (1-based, in prompt order) when several skills match a name
+    pub select: Option<usize>,
+    /// Keep an `InstallPlan` of each install instead of downloading or writing anything
+    pub dry_run: bool,
+    /// Save only the skill's SKILL.md
+    pub manifest_only: bool,
+}
+
+/// Where `resolve_and_install` puts the skill
+#[derive(Debug)]
+pub enum InstallDestination {
+    /// The target's skills directory, the one in the home directory with `global`
+    Target { target: TargetType, global: bool },
+    /// A custom directory; with `flat` the skill's files go directly into it
+    Dir { dir: PathBuf, flat: bool },
+    /// Every target and directory listed in a project's `.skills.toml`
+    Project(ProjectConfig),
+    /// A tarball of the skill folder, or just its SKILL.md, written to stdout
+    Stdout,
+}
+
+impl InstallDestination {
+    /// The locations from the `.skills.toml` in `start_dir` or the nearest parent with one
+    pub fn project(start_dir: &Path) -> Result<Self> {
+        load_project_config(start_dir)?
+            .map(Self::Project)
+            .ok_or_else(|| {
+                anyhow!(
+                    "--type is required for installation (or declare targets in {})",
+                    PROJECT_CONFIG_FILE
+                )
+            })
+    }
+}
+
+/// Outcome of one attempt to install a skill into one location
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallRecord {
+    pub skill: String,
+    /// Folder the skill was fetched from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Branch or tag the skill was fetched at
+    #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    /// Commit the ref pointed at, when it could be looked up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// `directory_checksum` of the installed files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<PathBuf>,
+    /// Combined size of the installed files
+    pub bytes: u64,
+    /// `installed`, `skipped` (by `--overwrite-policy skip`) or `failed`
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl InstallRecord {
+    /// Record of an install that failed before a skill and destination were known
+    pub fn failed(skill: &str, error: &anyhow::Error) -> Self {
+        Self {
+            skill: skill.to_string(),
+            url: None,
+            git_ref: None,
+            commit: None,
+            sha256: None,
+            target: None,
+            destination: None,
+            bytes: 0,
+            status: "failed",
+            error: Some(format!("{:#}", error)),
+        }
+    }
+}
+
+/// What an install would do, as previewed with `InstallOptions::dry_run`
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallPlan {
+    pub skill: String,
+    /// Folder the skill would be fetched from
+    pub url: String,
+    /// Branch or tag the skill would be fetched at
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    /// Commit the ref points at, when looked up
+    pub commit: Option<String>,
+    /// Path of the skill folder inside the repository
+    pub source_path: String,
+    pub target: Option<String>,
+    pub destination: PathBuf,
+    /// Files already at the destination would be overwritten
+    pub would_overwrite: bool,
+    /// The destination already exists and `--overwrite-policy skip` would leave it alone
+    pub would_skip: bool,
+    /// Only SKILL.md would be saved
+    pub manifest_only: bool,
+}
+
+/// Where `install_to` put a skill, with the branch it was fetched from
+struct Installed {
+    skill: ResolvedSkill,
+    dest_path: PathBuf,
+    /// The overwrite policy left an existing install in place
+    skipped: bool,
 }
 
 /// Service for installing skills
 pub struct SkillInstaller<D: GitHubDownloader, P: GitHubUrlParser> {
     downloader: D,
     url_parser: P,
+    options: InstallOptions,
+    records: RefCell<Vec<InstallRecord>>,
+    plans: RefCell<Vec<InstallPlan>>,
 }
 
 impl<D: GitHubDownloader, P: GitHubUrlParser> SkillInstaller<D, P> {
@@ -22,21 +220,195 @@ impl<D: GitHubDownloader, P: GitHubUrlParser> SkillInstaller<D, P> {
         Self {
             downloader,
             url_parser,
+            options: InstallOptions::default(),
+            records: RefCell::new(Vec::new()),
+            plans: RefCell::new(Vec::new()),
         }
     }
 
+    pub fn with_options(mut self, options: InstallOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Install attempts so far, when `InstallOptions::record` is set
+    pub fn install_records(&self) -> Vec<InstallRecord> {
+        self.records.borrow().clone()
+    }
+
+    /// Installs previewed so far, when `InstallOptions::dry_run` is set
+    pub fn install_plans(&self) -> Vec<InstallPlan> {
+        self.plans.borrow().clone()
+    }
+
     pub fn install_from_url<T: Target>(&self, url: &str, target: &T, global: bool) -> Result<()> {
-        let repo = self
-            .url_parser
-            .parse(url)
-            .context("Failed to parse GitHub URL")?;
-        let skill_name = extract_skill_name(&repo.path)?;
-        let target_dir = get_target_directory(target, global)?;
+        let skill = self.resolve_url(url)?;
+        self.install(&skill, target, global)
+    }
 
-        self.downloader
-            .download_folder(&repo, &target_dir, &skill_name)?;
+    /// Resolves `skill_or_url` and installs it into `destination`, as `skills install` does
+    pub fn resolve_and_install<C, S, U, A, B, I>(
+        &self,
+        skill_or_url: &str,
+        destination: &InstallDestination,
+        alias_service: &AliasService<C, P>,
+        skill_finder: &SkillFinder<S, U, A>,
+        api_client: &B,
+        user_interaction: &I,
+    ) -> Result<()>
+    where
+        C: ConfigStorage,
+        S: MarketStorage,
+        U: GitHubUrlParser,
+        A: GitHubApiClient,
+        B: GitHubApiClient,
+        I: UserInteraction,
+    {
+        let skill = self.resolve_source(
+            skill_or_url,
+            alias_service,
+            skill_finder,
+            api_client,
+            user_interaction,
+        )?;
 
-        Ok(())
+        let repo = format!("{}/{}", skill.repo.owner, skill.repo.repo);
+        let lookup_commit = |git_ref: &str| {
+            api_client.commit_sha(&repo, git_ref).unwrap_or_else(|e| {
+                eprintln!("Warning: Could not look up the commit: {:#}", e);
+                None
+            })
+        };
+
+        if self.options.dry_run {
+            self.install_resolved(&skill, destination)?;
+            let commit = lookup_commit(&skill.repo.branch);
+            for plan in self.plans.borrow_mut().iter_mut() {
+                plan.commit = commit.clone();
+            }
+            return Ok(());
+        }
+
+        let result = self.install_resolved(&skill, destination);
+
+        // Branch fallback can leave records at different refs, each looked up once
+        let mut commits = BTreeMap::new();
+        for record in self.records.borrow_mut().iter_mut() {
+            if let Some(git_ref) = record
+                .git_ref
+                .as_ref()
+                .filter(|_| record.status != "failed")
+            {
+                record.commit = commits
+                    .entry(git_ref.clone())
+                    .or_insert_with(|| lookup_commit(git_ref))
+                    .clone();
+            }
+        }
+        result
+    }
+
+    /// Resolves an alias, GitHub URL, bare `owner/repo` or market skill name to the skill
+    /// to install. A market name may end in `@<ref>` to install another branch or tag, or
+    /// in `@latest` for the newest release.
+    pub fn resolve_source<C, S, U, A, B, I>(
+        &self,
+        skill_or_url: &str,
+        alias_service: &AliasService<C, P>,
+        skill_finder: &SkillFinder<S, U, A>,
+        api_client: &B,
+        user_interaction: &I,
+    ) -> Result<ResolvedSkill>
+    where
+        C: ConfigStorage,
+        S: MarketStorage,
+        U: GitHubUrlParser,
+        A: GitHubApiClient,
+        B: GitHubApiClient,
+        I: UserInteraction,
+    {
+        let skill = match alias_service.resolve_source(skill_or_url)? {
+            Some(url) => self.resolve_url(&url)?,
+            None if bare_repo(skill_or_url).is_some() => {
+                self.resolve_repo(skill_or_url, api_client, user_interaction)?
+            }
+            None => {
+                let (name, git_ref) = match skill_or_url.split_once('@') {
+                    Some((name, git_ref)) if !git_ref.is_empty() => (name, Some(git_ref)),
+                    _ => (skill_or_url, None),
+                };
+                let mut skill = self.resolve_from_market(name, skill_finder, user_interaction)?;
+                match git_ref {
+                    Some(LATEST_REF) => {
+                        skill.repo.branch = LATEST_REF.to_string();
+                        skill
+                    }
+                    Some(git_ref) => self.pin_ref(skill, git_ref, api_client)?,
+                    None => skill,
+                }
+            }
+        };
+        self.pin_latest_release(skill, api_client)
+    }
+
+    /// Installs an already resolved skill into `destination`
+    pub fn install_resolved(
+        &self,
+        skill: &ResolvedSkill,
+        destination: &InstallDestination,
+    ) -> Result<()> {
+        let manifest_only = self.options.manifest_only;
+        match destination {
+            InstallDestination::Stdout => {
+                let mut stdout = std::io::stdout().lock();
+                if manifest_only {
+                    self.write_manifest(skill, &mut stdout)
+                } else {
+                    self.write_tarball(skill, &mut stdout)
+                }
+            }
+            InstallDestination::Dir { dir, flat } => {
+                if manifest_only {
+                    let dest_dir = if *flat {
+                        dir.clone()
+                    } else {
+                        dir.join(self.folder_name(skill))
+                    };
+                    self.install_manifest_to(skill, &dest_dir)
+                } else {
+                    self.install_into_dir(skill, dir, *flat, self.options.force)
+                }
+            }
+            InstallDestination::Target { target, global } => {
+                if manifest_only {
+                    self.install_manifest(skill, target, *global)
+                } else {
+                    self.install(skill, target, *global)
+                }
+            }
+            InstallDestination::Project(project_config) => {
+                eprintln!(
+                    "Installing into the locations from {}",
+                    project_config.root.join(PROJECT_CONFIG_FILE).display()
+                );
+                for target in &project_config.targets {
+                    if manifest_only {
+                        self.install_manifest_in_project(skill, target, &project_config.root)?;
+                    } else {
+                        self.install_in_project(skill, target, &project_config.root)?;
+                    }
+                }
+                for dir in &project_config.dirs {
+                    if manifest_only {
+                        let dest_dir = dir.join(self.folder_name(skill));
+                        self.install_manifest_to(skill, &dest_dir)?;
+                    } else {
+                        self.install_into_dir(skill, dir, false, self.options.force)?;
+                    }
+                }
+                Ok(())
+            }
+        }
     }
 
     pub fn install_from_market<S, U, A, I, T>(
@@ -54,42 +426,644 @@ impl<D: GitHubDownloader, P: GitHubUrlParser> SkillInstaller<D, P> {
         I: UserInteraction,
         T: Target,
     {
-        println!("Searching for skill '{}' in markets...\n", skill_name);
-        let matches = skill_finder.find_by_name(skill_name)?;
+        let skill = self.resolve_from_market(skill_name, skill_finder, user_interaction)?;
+        self.install(&skill, target, global)
+    }
 
-        if matches.is_empty() {
-            return Err(anyhow!(
-                "No available skill '{}' in the market. Please add the market first using 'skills market add <url>'",
-                skill_name
-            ));
-        }
+    pub fn resolve_url(&self, url: &str) -> Result<ResolvedSkill> {
+        let repo = self
+            .url_parser
+            .parse(url)
+            .context("Failed to parse GitHub URL")?;
 
-        let selected = self.select_skill(&matches, user_interaction)?;
+        // A bare repository URL is usually a mistake that would install the whole repository
+        let name = if repo.path.trim_matches('/').is_empty() {
+            if !self.options.allow_repo_root {
+                return Err(anyhow!(
+                    "{} points at the repository root, not a skill folder. Add /tree/<branch>/<skill-folder> to the URL, or pass --allow-repo-root to install the whole repository",
+                    url
+                ));
+            }
+            eprintln!(
+                "Warning: Installing the whole repository {} as a skill",
+                repo.repo
+            );
+            repo.repo.clone()
+        } else {
+            extract_skill_name(&repo.path)?
+        };
 
-        println!(
-            "Installing {} from {}...\n",
-            selected.name, selected.market_name
-        );
+        Ok(ResolvedSkill {
+            repo,
+            name,
+            market_name: None,
+        })
+    }
+
+    /// Finds the skill in the markets, asking the user to choose when several match.
+    /// Status output goes to stderr so that stdout stays usable for tarball output.
+    pub fn resolve_from_market<S, U, A, I>(
+        &self,
+        skill_name: &str,
+        skill_finder: &SkillFinder<S, U, A>,
+        user_interaction: &I,
+    ) -> Result<ResolvedSkill>
+    where
+        S: MarketStorage,
+        U: GitHubUrlParser,
+        A: GitHubApiClient,
+        I: UserInteraction,
+    {
+        eprintln!("Searching for skill '{}' in markets...\n", skill_name);
+
+        let selected = match skill_finder.find_by_name_or_similar(skill_name)? {
+            NameLookup::Exact(matches) | NameLookup::Similar(matches)
+                if !matches.is_empty() && self.options.select.is_some() =>
+            {
+                self.preset_choice(&matches)?.clone()
+            }
+            NameLookup::Exact(matches) if matches.len() == 1 => {
+                self.select_skill(&matches, user_interaction)?.clone()
+            }
+            NameLookup::Exact(matches) => {
+                let matches = skill_finder.with_descriptions(matches);
+                self.select_skill(&matches, user_interaction)?.clone()
+            }
+            NameLookup::Similar(matches) if !matches.is_empty() => {
+                eprintln!("No skill named '{}', but found similar skills.", skill_name);
+                let matches = skill_finder.with_descriptions(matches);
+                user_interaction.select_skill(&matches)?.clone()
+            }
+            NameLookup::Similar(_) => {
+                return Err(anyhow!(
+                    "No available skill '{}' in the market. Please add the market first using 'skills market add <url>'",
+                    skill_name
+                ));
+            }
+        };
 
         let repo = self
             .url_parser
             .parse(&selected.url)
             .context("Failed to parse skill URL")?;
-        let target_dir = get_target_directory(target, global)?;
 
-        self.downloader
-            .download_folder(&repo, &target_dir, &selected.name)?;
+        Ok(ResolvedSkill {
+            repo,
+            name: selected.name,
+            market_name: Some(selected.market_name),
+        })
+    }
+
+    /// Resolves a bare `owner/repo` to the skill at the root of the repository's default
+    /// branch, or under its `skills/` folder, asking the user to choose when `skills/`
+    /// holds several
+    pub fn resolve_repo<A: GitHubApiClient, I: UserInteraction>(
+        &self,
+        repo_path: &str,
+        api_client: &A,
+        user_interaction: &I,
+    ) -> Result<ResolvedSkill> {
+        let branch = api_client.default_branch(repo_path)?;
+        let has_manifest = |path: &str| -> Result<bool> {
+            Ok(api_client
+                .get_directory_contents(repo_path, path)?
+                .iter()
+                .any(|item| item.item_type == "file" && item.name == MANIFEST_FILE))
+        };
+
+        let path = if has_manifest("")? {
+            String::new()
+        } else if has_manifest("skills").unwrap_or(false) {
+            "skills".to_string()
+        } else {
+            let folders = api_client
+                .get_directory_contents(repo_path, "skills")
+                .with_context(|| {
+                    format!(
+                        "{} has no {} at its root and no skills/ folder; pass the URL of the skill folder instead",
+                        repo_path, MANIFEST_FILE
+                    )
+                })?
+                .into_iter()
+                .filter(|item| item.item_type == "dir")
+                .map(|item| SkillMatch {
+                    url: format!(
+                        "https://github.com/{}/tree/{}/{}",
+                        repo_path, branch, item.path
+                    ),
+                    name: item.name,
+                    market_name: repo_path.to_string(),
+                    repo: repo_path.to_string(),
+                    path: item.path,
+                    description: None,
+                    updated: None,
+                })
+                .collect::<Vec<_>>();
+
+            let selected = match folders.len() {
+                0 => {
+                    return Err(anyhow!(
+                        "{} has no skills at its root or under skills/; pass the URL of the skill folder instead",
+                        repo_path
+                    ));
+                }
+                _ if self.options.select.is_some() => self.preset_choice(&folders)?,
+                _ => self.select_skill(&folders, user_interaction)?,
+            };
+            selected.path.clone()
+        };
+
+        let url = format!("https://github.com/{}/tree/{}/{}", repo_path, branch, path);
+        let repo = self
+            .url_parser
+            .parse(url.trim_end_matches('/'))
+            .context("Failed to parse GitHub URL")?;
+        let name = if path.is_empty() {
+            repo.repo.clone()
+        } else {
+            extract_skill_name(&path)?
+        };
+
+        eprintln!("Installing {} from {} ({})", name, repo_path, branch);
+        Ok(ResolvedSkill {
+            repo,
+            name,
+            market_name: None,
+        })
+    }
+
+    /// Replaces the `latest` ref with the tag of the repository's newest release
+    pub fn pin_latest_release<A: GitHubApiClient>(
+        &self,
+        mut skill: ResolvedSkill,
+        api_client: &A,
+    ) -> Result<ResolvedSkill> {
+        if skill.repo.branch != LATEST_REF {
+            return Ok(skill);
+        }
+
+        let repo = format!("{}/{}", skill.repo.owner, skill.repo.repo);
+        let tag = api_client.latest_release_tag(&repo)?.ok_or_else(|| {
+            anyhow!(
+                "{} has no releases, so '{}' cannot be resolved; install a branch instead (e.g. {}@main)",
+                repo,
+                LATEST_REF,
+                skill.name
+            )
+        })?;
+
+        eprintln!("Resolved latest release of {} to {}", repo, tag);
+        skill.repo.branch = tag;
+        skill.repo.is_tag = true;
+        Ok(skill)
+    }
+
+    /// Points the skill at `git_ref`, a branch or else a tag, with branch fallback off so a
+    /// ref that does not exist fails instead of installing another branch
+    pub fn pin_ref<A: GitHubApiClient>(
+        &self,
+        mut skill: ResolvedSkill,
+        git_ref: &str,
+        api_client: &A,
+    ) -> Result<ResolvedSkill> {
+        let repo = format!("{}/{}", skill.repo.owner, skill.repo.repo);
+        skill.repo.branch = git_ref.to_string();
+        if api_client.branch_exists(&repo, git_ref)? {
+            skill.repo.pinned = true;
+        } else {
+            eprintln!(
+                "No branch '{}' in {}; installing it as a tag",
+                git_ref, repo
+            );
+            skill.repo.is_tag = true;
+        }
+        Ok(skill)
+    }
+
+    /// Name of the folder the skill is installed into: the skill name, suffixed with
+    /// `@<ref>` for versioned installs
+    pub fn folder_name(&self, skill: &ResolvedSkill) -> String {
+        if self.options.versioned {
+            // Branch names such as `release/1.0` must not create nested folders
+            format!("{}@{}", skill.name, skill.repo.branch.replace('/', "-"))
+        } else {
+            skill.name.clone()
+        }
+    }
+
+    /// Folder the skill is installed into for `target`
+    pub fn installed_path<T: Target>(
+        &self,
+        skill: &ResolvedSkill,
+        target: &T,
+        global: bool,
+    ) -> Result<PathBuf> {
+        Ok(get_target_directory(target, global)?.join(self.folder_name(skill)))
+    }
+
+    /// Writes a unified diff from the files installed at `installed` to the current
+    /// upstream version, returning how many files differ
+    pub fn diff(
+        &self,
+        skill: &ResolvedSkill,
+        installed: &Path,
+        out: &mut dyn Write,
+    ) -> Result<usize> {
+        if !installed.is_dir() {
+            return Err(anyhow!(
+                "'{}' is not installed at {}",
+                skill.name,
+                installed.display()
+            ));
+        }
+
+        let upstream = self.downloader.fetch_folder(&skill.repo)?;
+        diff_dirs(installed, &upstream.path, out)
+    }
+
+    /// Copies the skill folder into the target's skills directory
+    pub fn install<T: Target>(
+        &self,
+        skill: &ResolvedSkill,
+        target: &T,
+        global: bool,
+    ) -> Result<()> {
+        self.install_into_skills_dir(skill, target, &get_target_directory(target, global)?)
+    }
+
+    /// Copies the skill folder into the target's skills directory in the project rooted at
+    /// `project_root`, which may be above the current directory
+    pub fn install_in_project<T: Target>(
+        &self,
+        skill: &ResolvedSkill,
+        target: &T,
+        project_root: &Path,
+    ) -> Result<()> {
+        self.install_into_skills_dir(skill, target, &project_skills_dir(target, project_root))
+    }
+
+    fn install_into_skills_dir<T: Target>(
+        &self,
+        skill: &ResolvedSkill,
+        target: &T,
+        skills_dir: &Path,
+    ) -> Result<()> {
+        let dest_for = |skill: &ResolvedSkill| skills_dir.join(self.folder_name(skill));
+        if self.options.dry_run {
+            return self.plan(skill, Some(target), &dest_for(skill), false);
+        }
+        let (skill, dest_path, result) = match self.install_to(skill, &dest_for) {
+            Ok(installed) => (installed.skill, installed.dest_path, Ok(installed.skipped)),
+            Err(e) => (skill.clone(), dest_for(skill), Err(e)),
+        };
+        self.record(&skill, Some(target), &dest_path, result.as_ref().copied());
+        result.map(|_| ())
+    }
+
+    /// Copies the skill folder into `dir`: as a `<skill-name>` subfolder, or with `flat`
+    /// its files directly into `dir`, which must then be empty unless `force` is set
+    pub fn install_into_dir(
+        &self,
+        skill: &ResolvedSkill,
+        dir: &Path,
+        flat: bool,
+        force: bool,
+    ) -> Result<()> {
+        let dest_for = |skill: &ResolvedSkill| {
+            if flat {
+                dir.to_path_buf()
+            } else {
+                dir.join(self.folder_name(skill))
+            }
+        };
+
+        let (skill, dest_path, result) = if flat && !force && !is_empty_dir(dir)? {
+            (
+                skill.clone(),
+                dest_for(skill),
+                Err(anyhow!(
+                    "Destination '{}' is not empty; use --force to install into it anyway",
+                    dir.display()
+                )),
+            )
+        } else if self.options.dry_run {
+            return self.plan(skill, None::<&TargetType>, &dest_for(skill), false);
+        } else {
+            match self.install_to(skill, &dest_for) {
+                Ok(installed) => (installed.skill, installed.dest_path, Ok(installed.skipped)),
+                Err(e) => (skill.clone(), dest_for(skill), Err(e)),
+            }
+        };
+        self.record(
+            &skill,
+            None::<&TargetType>,
+            &dest_path,
+            result.as_ref().copied(),
+        );
+        result.map(|_| ())
+    }
+
+    /// Fetches the skill and copies it into the folder `dest_for` names for it. The name is
+    /// taken once the branch is known, so a versioned install is labelled with the branch
+    /// actually fetched after branch fallback.
+    fn install_to(
+        &self,
+        skill: &ResolvedSkill,
+        dest_for: &dyn Fn(&ResolvedSkill) -> PathBuf,
+    ) -> Result<Installed> {
+        if let Some(market_name) = &skill.market_name {
+            println!("Installing {} from {}...\n", skill.name, market_name);
+        }
+
+        let requested_path = dest_for(skill);
+        if self.skips_existing(&requested_path) {
+            return Ok(Installed {
+                skill: skill.clone(),
+                dest_path: requested_path,
+                skipped: true,
+            });
+        }
+
+        let folder = self.downloader.fetch_folder(&skill.repo)?;
 
+        // Checked against the branch actually fetched; a skill without a SKILL.md
+        // declares no requirement
+        if let Some(manifest) = &folder.manifest {
+            self.check_cli_version(skill, manifest)?;
+        }
+
+        let mut fetched = skill.clone();
+        fetched.repo.branch = folder.branch.clone();
+        let dest_path = dest_for(&fetched);
+        if dest_path != requested_path && self.skips_existing(&dest_path) {
+            return Ok(Installed {
+                skill: fetched,
+                dest_path,
+                skipped: true,
+            });
+        }
+
+        self.downloader.install_folder(&folder, &dest_path)?;
+
+        if self.options.print_tree {
+            print_tree(&dest_path, self.options.max_depth)?;
+        }
+
+        if self.options.checksum {
+            println!("Checksum (sha256): {}", directory_checksum(&dest_path)?);
+        }
+
+        Ok(Installed {
+            skill: fetched,
+            dest_path,
+            skipped: false,
+        })
+    }
+
+    /// Whether the overwrite policy leaves the install at `dest_path` alone, saying so
+    fn skips_existing(&self, dest_path: &Path) -> bool {
+        let skips = self.downloader.skips_existing(dest_path);
+        if skips {
+            println!(
+                "Skipping: {} already exists (--overwrite-policy skip)",
+                dest_path.display()
+            );
+        }
+        skips
+    }
+
+    /// Writes the skill folder as a tarball rooted at the skill name
+    pub fn write_tarball(&self, skill: &ResolvedSkill, out: &mut dyn Write) -> Result<()> {
+        self.downloader.write_tarball(&skill.repo, &skill.name, out)
+    }
+
+    /// Saves only the skill's SKILL.md into the target's skills directory
+    pub fn install_manifest<T: Target>(
+        &self,
+        skill: &ResolvedSkill,
+        target: &T,
+        global: bool,
+    ) -> Result<()> {
+        self.install_manifest_into_skills_dir(skill, target, &get_target_directory(target, global)?)
+    }
+
+    /// Saves only the skill's SKILL.md into the target's skills directory in the project
+    /// rooted at `project_root`
+    pub fn install_manifest_in_project<T: Target>(
+        &self,
+        skill: &ResolvedSkill,
+        target: &T,
+        project_root: &Path,
+    ) -> Result<()> {
+        self.install_manifest_into_skills_dir(
+            skill,
+            target,
+            &project_skills_dir(target, project_root),
+        )
+    }
+
+    fn install_manifest_into_skills_dir<T: Target>(
+        &self,
+        skill: &ResolvedSkill,
+        target: &T,
+        skills_dir: &Path,
+    ) -> Result<()> {
+        let dest_dir = skills_dir.join(self.folder_name(skill));
+        if self.options.dry_run {
+            return self.plan(skill, Some(target), &dest_dir, true);
+        }
+        let result = self.save_manifest(skill, &dest_dir);
+        self.record(
+            skill,
+            Some(target),
+            &dest_dir,
+            result.as_ref().map(|_| false),
+        );
+        result
+    }
+
+    /// Saves only the skill's SKILL.md into `dest_dir`
+    pub fn install_manifest_to(&self, skill: &ResolvedSkill, dest_dir: &Path) -> Result<()> {
+        if self.options.dry_run {
+            return self.plan(skill, None::<&TargetType>, dest_dir, true);
+        }
+        let result = self.save_manifest(skill, dest_dir);
+        self.record(
+            skill,
+            None::<&TargetType>,
+            dest_dir,
+            result.as_ref().map(|_| false),
+        );
+        result
+    }
+
+    /// Adds the outcome of installing `skill` into `dest_path` to the records, where
+    /// `result` holds whether the overwrite policy skipped the install
+    fn record<T: Target>(
+        &self,
+        skill: &ResolvedSkill,
+        target: Option<&T>,
+        dest_path: &Path,
+        result: Result<bool, &anyhow::Error>,
+    ) {
+        if !self.options.record {
+            return;
+        }
+
+        let installed = result.is_ok();
+        let record = InstallRecord {
+            skill: skill.name.clone(),
+            url: Some(skill.repo.tree_url()),
+            git_ref: Some(skill.repo.branch.clone()),
+            commit: None,
+            sha256: installed
+                .then(|| directory_checksum(dest_path).ok())
+                .flatten(),
+            target: target.map(|target| target.as_str().to_string()),
+            destination: Some(dest_path.to_path_buf()),
+            bytes: if installed {
+                folder_usage(dest_path).map_or(0, |(size, _)| size)
+            } else {
+                0
+            },
+            status: match result {
+                Ok(false) => "installed",
+                Ok(true) => "skipped",
+                Err(_) => "failed",
+            },
+            error: result.err().map(|e| format!("{:#}", e)),
+        };
+        self.records.borrow_mut().push(record);
+    }
+
+    /// Adds what installing `skill` into `dest_path` would do to the plans, touching
+    /// neither the network nor the destination
+    fn plan<T: Target>(
+        &self,
+        skill: &ResolvedSkill,
+        target: Option<&T>,
+        dest_path: &Path,
+        manifest_only: bool,
+    ) -> Result<()> {
+        let would_skip = !manifest_only && self.downloader.skips_existing(dest_path);
+        let would_overwrite = if manifest_only {
+            dest_path.join(MANIFEST_FILE).exists()
+        } else {
+            !would_skip && !is_empty_dir(dest_path)?
+        };
+
+        self.plans.borrow_mut().push(InstallPlan {
+            skill: skill.name.clone(),
+            url: skill.repo.tree_url(),
+            git_ref: skill.repo.branch.clone(),
+            commit: None,
+            source_path: skill.repo.path.clone(),
+            target: target.map(|target| target.as_str().to_string()),
+            destination: dest_path.to_path_buf(),
+            would_overwrite,
+            would_skip,
+            manifest_only,
+        });
+        Ok(())
+    }
+
+    fn save_manifest(&self, skill: &ResolvedSkill, dest_dir: &Path) -> Result<()> {
+        let manifest = self.downloader.download_manifest(&skill.repo)?;
+        self.check_cli_version(skill, &manifest)?;
+
+        fs::create_dir_all(dest_dir).context("Failed to create skill directory")?;
+
+        let dest_path = dest_dir.join(MANIFEST_FILE);
+        fs::write(&dest_path, manifest).context("Failed to write SKILL.md")?;
+
+        println!("Saved {} to: {}", MANIFEST_FILE, dest_path.display());
+        Ok(())
+    }
+
+    /// Writes only the skill's SKILL.md
+    pub fn write_manifest(&self, skill: &ResolvedSkill, out: &mut dyn Write) -> Result<()> {
+        let manifest = self.downloader.download_manifest(&skill.repo)?;
+        out.write_all(&manifest)
+            .context("Failed to write SKILL.md")?;
         Ok(())
     }
 
+    /// Refuses skills whose SKILL.md requires a newer CLI, or only warns with `force`
+    fn check_cli_version(&self, skill: &ResolvedSkill, manifest: &[u8]) -> Result<()> {
+        let content = String::from_utf8_lossy(manifest);
+        let Some(required) = manifest::min_cli_version(&content) else {
+            return Ok(());
+        };
+
+        let Some(required_version) = parse_lenient_version(&required) else {
+            eprintln!(
+                "Warning: Ignoring unparsable min_cli_version '{}' in {}",
+                required, skill.name
+            );
+            return Ok(());
+        };
+
+        let current = env!("CARGO_PKG_VERSION");
+        // The crate version always parses
+        let current_version = semver::Version::parse(current)?;
+        if current_version >= required_version {
+            return Ok(());
+        }
+
+        if self.options.force {
+            eprintln!(
+                "Warning: {} requires skills {} or newer (this is {}); installing anyway",
+                skill.name, required, current
+            );
+            return Ok(());
+        }
+
+        Err(anyhow!(
+            "{} requires skills {} or newer, but this is {}. Upgrade the CLI, or pass --force to install anyway",
+            skill.name,
+            required,
+            current
+        ))
+    }
+
+    /// Picks the match numbered by `InstallOptions::select` without prompting
+    fn preset_choice<'a>(&self, matches: &'a [SkillMatch]) -> Result<&'a SkillMatch> {
+        let choice = self.options.select.unwrap_or(1);
+
+        if matches.len() == 1 {
+            return Err(anyhow!(
+                "Only one skill matches ({} from {}), so --select is not needed",
+                matches[0].name,
+                matches[0].market_name
+            ));
+        }
+
+        let selected = choice
+            .checked_sub(1)
+            .and_then(|i| matches.get(i))
+            .ok_or_else(|| {
+                anyhow!(
+                    "--select {} is out of range; {} skills match (1-{})",
+                    choice,
+                    matches.len(),
+                    matches.len()
+                )
+            })?;
+
+        eprintln!(
+            "Selected skill {}: {} ({})",
+            choice, selected.name, selected.market_name
+        );
+        Ok(selected)
+    }
+
     fn select_skill<'a, I: UserInteraction>(
         &self,
         matches: &'a [SkillMatch],
         user_interaction: &I,
     ) -> Result<&'a SkillMatch> {
         if matches.len() == 1 {
-            println!(
+            eprintln!(
                 "Found skill: {} ({})",
                 matches[0].name, matches[0].market_name
             );
@@ -100,18 +1074,473 @@ impl<D: GitHubDownloader, P: GitHubUrlParser> SkillInstaller<D, P> {
     }
 }
 
-fn get_target_directory<T: Target>(target: &T, global: bool) -> Result<PathBuf> {
-    let base_dir = if global {
-        dirs::home_dir().ok_or_else(|| anyhow!("Could not determine home directory"))?
-    } else {
-        std::env::current_dir().context("Failed to get current directory")?
+/// A skill folder found in one of the targets' skills directories
+#[derive(Debug, Clone)]
+pub struct InstalledSkill {
+    pub name: String,
+    pub target: TargetType,
+    /// Whether it lives under the home directory rather than the current project
+    pub global: bool,
+    pub path: PathBuf,
+    /// Combined size of the skill's files in bytes
+    pub size: u64,
+    /// Latest modification time of any of the skill's files
+    pub modified: Option<SystemTime>,
+}
+
+/// A skill folder found in a target's skills directory, as printed by `list --json`
+#[derive(Debug, Clone, Serialize)]
+pub struct ListedSkill {
+    pub name: String,
+    pub path: PathBuf,
+    pub target: String,
+    /// Installs record no source, so this stays empty until they do
+    pub source_url: Option<String>,
+    /// The `@<ref>` suffix of a versioned install's folder name
+    pub version: Option<String>,
+}
+
+impl ListedSkill {
+    /// Describes the folder `folder_name` in `skills_dir`
+    pub fn new<T: Target>(folder_name: &str, skills_dir: &Path, target: &T) -> Self {
+        let (name, version) = match folder_name.split_once('@') {
+            Some((name, version)) => (name, Some(version.to_string())),
+            None => (folder_name, None),
+        };
+        Self {
+            name: name.to_string(),
+            path: skills_dir.join(folder_name),
+            target: target.as_str().to_string(),
+            source_url: None,
+            version,
+        }
+    }
+}
+
+/// Names of the skill folders in the target's skills directory, sorted, together with
+/// that directory. A directory that does not exist yet holds no skills.
+pub fn installed_skill_names<T: Target>(
+    target: &T,
+    global: bool,
+) -> Result<(PathBuf, Vec<String>)> {
+    let skills_dir = get_target_directory(target, global)?;
+    if !skills_dir.is_dir() {
+        return Ok((skills_dir, Vec::new()));
+    }
+
+    let mut names = fs::read_dir(&skills_dir)
+        .with_context(|| format!("Failed to read {}", skills_dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    names.sort();
+    Ok((skills_dir, names))
+}
+
+/// Deletes the skill folder `name` from the target's skills directory, returning the
+/// removed path. Read-only installs are made writable first. `name` has to be a single
+/// folder name, so nothing outside the skills directory can be removed.
+pub fn uninstall<T: Target, F: FileSystem>(
+    name: &str,
+    target: &T,
+    global: bool,
+    file_system: &F,
+) -> Result<PathBuf> {
+    let is_folder_name = matches!(
+        Path::new(name).components().collect::<Vec<_>>()[..],
+        [std::path::Component::Normal(_)]
+    );
+    if !is_folder_name {
+        return Err(anyhow!(
+            "'{}' is not a skill name; pass the name of a folder in the skills directory",
+            name
+        ));
+    }
+
+    let skills_dir = get_target_directory(target, global)?;
+    let path = skills_dir.join(name);
+
+    let metadata = fs::symlink_metadata(&path).ok();
+    if !metadata.as_ref().is_some_and(|m| m.is_dir()) {
+        // Versioned installs live in `<name>@<ref>` folders
+        let prefix = format!("{}@", name);
+        let mut versions = fs::read_dir(&skills_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .filter(|folder| folder.starts_with(&prefix))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        versions.sort();
+
+        if !versions.is_empty() {
+            return Err(anyhow!(
+                "'{}' is only installed as versioned folders ({}); uninstall one of them by that name",
+                name,
+                versions.join(", ")
+            ));
+        }
+        return Err(anyhow!(
+            "'{}' is not installed in {}",
+            name,
+            skills_dir.display()
+        ));
+    }
+
+    // The resolved folder must still sit directly in the skills directory
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", path.display()))?;
+    if canonical.parent() != Some(&skills_dir.canonicalize()?) {
+        return Err(anyhow!(
+            "Refusing to remove {}: it resolves outside {}",
+            path.display(),
+            skills_dir.display()
+        ));
+    }
+
+    file_system.set_read_only(&path, false)?;
+    file_system.remove_dir_all(&path)?;
+    Ok(path)
+}
+
+/// Deletes every skill folder in the target's skills directory whose name matches `glob`,
+/// returning the removed paths. The matches are listed and confirmed through
+/// `user_interaction` unless `yes` is set; a glob matching every installed skill has to
+/// be confirmed by typing how many skills that is. Nothing is removed when declined.
+pub fn uninstall_matching<T: Target, F: FileSystem, I: UserInteraction>(
+    glob: &str,
+    target: &T,
+    global: bool,
+    yes: bool,
+    file_system: &F,
+    user_interaction: &I,
+) -> Result<Vec<PathBuf>> {
+    let matcher = Glob::new(glob)
+        .with_context(|| format!("Invalid pattern '{}'", glob))?
+        .compile_matcher();
+    let (skills_dir, names) = installed_skill_names(target, global)?;
+    let matched = names
+        .iter()
+        .filter(|name| matcher.is_match(name))
+        .collect::<Vec<_>>();
+    if matched.is_empty() {
+        return Err(anyhow!(
+            "No installed skills in {} match '{}'",
+            skills_dir.display(),
+            glob
+        ));
+    }
+
+    eprintln!(
+        "'{}' matches {} skill(s) in {}:",
+        glob,
+        matched.len(),
+        skills_dir.display()
+    );
+    for name in &matched {
+        eprintln!("  {}", name);
+    }
+
+    if !yes {
+        let confirmed = if matched.len() > 1 && matched.len() == names.len() {
+            let count = matched.len().to_string();
+            user_interaction.confirm(
+                &format!(
+                    "This removes every installed skill; type {} to confirm:",
+                    count
+                ),
+                &count,
+            )?
+        } else {
+            user_interaction.confirm("Remove them? [y/N]", "y")?
+        };
+        if !confirmed {
+            return Ok(Vec::new());
+        }
+    }
+
+    matched
+        .iter()
+        .map(|name| uninstall(name, target, global, file_system))
+        .collect()
+}
+
+/// Finds the skills installed for every target, project-local ones first
+pub fn list_installed() -> Result<Vec<InstalledSkill>> {
+    let mut installed: Vec<InstalledSkill> = Vec::new();
+
+    for global in [false, true] {
+        for target in TargetType::value_variants() {
+            let skills_dir = get_target_directory(target, global)?;
+            // Run from the home directory, local and global folders are the same
+            if installed
+                .iter()
+                .any(|skill| skill.path.parent() == Some(&skills_dir))
+            {
+                continue;
+            }
+            if !skills_dir.is_dir() {
+                continue;
+            }
+
+            let mut entries = fs::read_dir(&skills_dir)
+                .with_context(|| format!("Failed to read {}", skills_dir.display()))?
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .collect::<Vec<_>>();
+            entries.sort_by_key(|entry| entry.file_name());
+
+            for entry in entries {
+                let (size, modified) = folder_usage(&entry.path())?;
+                installed.push(InstalledSkill {
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    target: *target,
+                    global,
+                    path: entry.path(),
+                    size,
+                    modified,
+                });
+            }
+        }
+    }
+
+    Ok(installed)
+}
+
+/// Total file size under `dir` and the newest file modification time
+fn folder_usage(dir: &Path) -> Result<(u64, Option<SystemTime>)> {
+    let mut size = 0;
+    let mut modified = None;
+
+    for entry in WalkDir::new(dir) {
+        let entry = entry.context("Failed to read installed files")?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let metadata = entry.metadata()?;
+        size += metadata.len();
+        if let Ok(time) = metadata.modified() {
+            modified = modified.max(Some(time));
+        }
+    }
+
+    Ok((size, modified))
+}
+
+/// Prints per-target counts, the total disk usage and the most recently updated skill.
+/// How many have updates is left out: installs record no source commit to compare, and
+/// there is no `outdated` command to ask.
+pub fn print_stats(installed: &[InstalledSkill]) {
+    if installed.is_empty() {
+        println!("No skills installed");
+        return;
+    }
+
+    println!("Installed skills:");
+    for target in TargetType::value_variants() {
+        let count = |global: bool| {
+            installed
+                .iter()
+                .filter(|skill| skill.target == *target && skill.global == global)
+                .count()
+        };
+        let (local, global) = (count(false), count(true));
+        if local + global > 0 {
+            println!(
+                "  {:<8} {} local, {} global",
+                target.as_str(),
+                local,
+                global
+            );
+        }
+    }
+
+    let total: u64 = installed.iter().map(|skill| skill.size).sum();
+    println!(
+        "\nTotal: {} skill(s), {:.1} MiB",
+        installed.len(),
+        total as f64 / (1024.0 * 1024.0)
+    );
+
+    if let Some(latest) = installed
+        .iter()
+        .filter(|skill| skill.modified.is_some())
+        .max_by_key(|skill| skill.modified)
+    {
+        let age = latest
+            .modified
+            .and_then(|time| SystemTime::now().duration_since(time).ok())
+            .unwrap_or_default();
+        println!(
+            "Most recently updated: {} ({}, {}) {}",
+            latest.name,
+            latest.target.as_str(),
+            if latest.global { "global" } else { "local" },
+            format_age(age)
+        );
+    }
+}
+
+/// Renders an age as "N <unit>(s) ago" using the largest whole unit
+pub(crate) fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    let (amount, unit) = match secs {
+        0..60 => return "just now".to_string(),
+        60..3600 => (secs / 60, "minute"),
+        3600..86400 => (secs / 3600, "hour"),
+        _ => (secs / 86400, "day"),
     };
+    format!("{} {}(s) ago", amount, unit)
+}
 
-    let folder_name = if target.as_str() == "copilot" {
-        ".github".to_string()
-    } else {
-        format!(".{}", target.as_str())
+/// Parses a version such as `1.2.3`, `v1.2` or `1`, filling in missing components with zero
+fn parse_lenient_version(version: &str) -> Option<semver::Version> {
+    let version = version.trim().trim_start_matches('v');
+    let components = version.split('.').count();
+    let padded = match components {
+        1 => format!("{}.0.0", version),
+        2 => format!("{}.0", version),
+        _ => version.to_string(),
     };
+    semver::Version::parse(&padded).ok()
+}
+
+/// Global skills live under the home directory on every platform: on Windows each
+/// supported tool reads `%USERPROFILE%\.<tool>`, not `%APPDATA%`
+fn get_target_directory<T: Target>(target: &T, global: bool) -> Result<PathBuf> {
+    if !global {
+        let current_dir = std::env::current_dir().context("Failed to get current directory")?;
+        return Ok(project_skills_dir(target, &current_dir));
+    }
+
+    let home_dir = dirs::home_dir().ok_or_else(|| anyhow!("Could not determine home directory"))?;
+    Ok(home_dir.join(target.global_folder()).join("skills"))
+}
+
+/// The target's project-local skills directory under `project_root`
+fn project_skills_dir<T: Target>(target: &T, project_root: &Path) -> PathBuf {
+    project_root.join(target.local_folder()).join("skills")
+}
+
+fn is_empty_dir(dir: &Path) -> Result<bool> {
+    if !dir.exists() {
+        return Ok(true);
+    }
+
+    let mut entries =
+        fs::read_dir(dir).with_context(|| format!("Failed to read directory {}", dir.display()))?;
+    Ok(entries.next().is_none())
+}
+
+/// SHA-256 over every file under `dir`: for each file in sorted relative-path order, the
+/// path (with `/` separators) and the SHA-256 of its contents. Independent of file order
+/// on disk and of timestamps, so the same files always give the same checksum.
+pub fn directory_checksum(dir: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+
+    for relative in relative_files(dir)? {
+        let contents = fs::read(dir.join(&relative))
+            .with_context(|| format!("Failed to read {}", relative.display()))?;
+
+        let path = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+        hasher.update(Sha256::digest(&contents));
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Relative paths of every file under `root`
+fn relative_files(root: &Path) -> Result<BTreeSet<PathBuf>> {
+    let mut files = BTreeSet::new();
+    for entry in WalkDir::new(root).min_depth(1) {
+        let entry = entry.context("Failed to read skill files")?;
+        if entry.file_type().is_file() {
+            files.insert(entry.path().strip_prefix(root)?.to_path_buf());
+        }
+    }
+    Ok(files)
+}
+
+/// Writes the changes from `old` to `new`: added and removed paths, unified diffs for
+/// modified text files and a note for modified binary files. Returns how many files differ.
+fn diff_dirs(old: &Path, new: &Path, out: &mut dyn Write) -> Result<usize> {
+    let old_files = relative_files(old)?;
+    let new_files = relative_files(new)?;
+
+    let mut changed = 0;
+    for path in old_files.union(&new_files) {
+        let display = path.to_string_lossy();
+
+        let (old_bytes, new_bytes) = match (old_files.contains(path), new_files.contains(path)) {
+            (true, false) => {
+                writeln!(out, "Removed: {}", display)?;
+                changed += 1;
+                continue;
+            }
+            (false, true) => {
+                writeln!(out, "Added: {}", display)?;
+                changed += 1;
+                continue;
+            }
+            _ => (fs::read(old.join(path))?, fs::read(new.join(path))?),
+        };
+
+        if old_bytes == new_bytes {
+            continue;
+        }
+        changed += 1;
+
+        match (
+            std::str::from_utf8(&old_bytes),
+            std::str::from_utf8(&new_bytes),
+        ) {
+            (Ok(old_text), Ok(new_text)) => {
+                let diff = TextDiff::from_lines(old_text, new_text);
+                write!(
+                    out,
+                    "{}",
+                    diff.unified_diff()
+                        .header(&format!("a/{}", display), &format!("b/{}", display))
+                )?;
+            }
+            _ => writeln!(out, "Binary file {} differs", display)?,
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Prints the files and directories under `root` as an indented tree
+fn print_tree(root: &Path, max_depth: Option<usize>) -> Result<()> {
+    println!("\n{}", root.display());
+
+    let mut walker = WalkDir::new(root).min_depth(1).sort_by_file_name();
+    if let Some(depth) = max_depth {
+        walker = walker.max_depth(depth);
+    }
+
+    for entry in walker {
+        let entry = entry.context("Failed to read installed files")?;
+        let indent = "  ".repeat(entry.depth());
+        let name = entry.file_name().to_string_lossy();
+
+        if entry.file_type().is_dir() {
+            println!("{}{}/", indent, name);
+        } else {
+            println!("{}{}", indent, name);
+        }
+    }
 
-    Ok(base_dir.join(folder_name).join("skills"))
+    Ok(())
 }
@@ -0,0 +1,108 @@
+//! Library API for managing skills.
+//!
+//! The service types are generic over the traits in each module so callers can swap
+//! in their own implementations. The free functions below wire up the default
+//! implementations for the common cases, mirroring the `skills` binary run without
+//! flags: they read the config file in the default config directory and apply its
+//! branch fallback, retry, headers and max concurrency, but offer none of the
+//! command-line options such as `--config` or waiting out rate limits.
+
+use anyhow::Result;
+
+pub mod config;
+pub mod github;
+pub mod installer;
+pub mod manifest;
+pub mod market;
+pub mod models;
+pub mod retry;
+pub mod skill_finder;
+
+use config::{AliasService, ConfigStorage, FileConfigStorage};
+use github::{
+    DefaultFileSystem, DefaultGitHubDownloader, DefaultGitHubUrlParser, DownloadOptions,
+    default_branch_fallback, parse_headers,
+};
+use market::{DefaultGitHubApiClient, FileMarketStorage};
+use models::{Config, SkillMatch};
+use skill_finder::{ConsoleUserInteraction, SearchOptions};
+
+pub use installer::{
+    InstallDestination, InstallOptions, InstallPlan, SkillInstaller, Target, TargetType,
+};
+pub use market::MarketService;
+pub use skill_finder::SkillFinder;
+
+/// Installs a skill by name, alias, `owner/repo` or GitHub URL into the target's skills
+/// directory. A market name may end in `@<ref>` or `@latest` as on the command line.
+pub fn install(skill_or_url: &str, target: TargetType, global: bool) -> Result<()> {
+    install_with(
+        skill_or_url,
+        &InstallDestination::Target { target, global },
+        InstallOptions::default(),
+    )?;
+    Ok(())
+}
+
+/// Installs a skill into `destination` with `options`, the way `skills install` does.
+/// Use `InstallDestination::project` for the locations in a `.skills.toml`. Returns the
+/// previewed installs when `options.dry_run` is set, and nothing otherwise.
+pub fn install_with(
+    skill_or_url: &str,
+    destination: &InstallDestination,
+    options: InstallOptions,
+) -> Result<Vec<InstallPlan>> {
+    let config = FileConfigStorage::new()?.load()?;
+    let url_parser = DefaultGitHubUrlParser;
+    let downloader =
+        DefaultGitHubDownloader::new(DefaultFileSystem).with_options(DownloadOptions {
+            branch_fallback: config
+                .branch_fallback
+                .clone()
+                .unwrap_or_else(default_branch_fallback),
+            retry: config.retry.unwrap_or_default(),
+            headers: parse_headers(&config.headers)?,
+            ..DownloadOptions::default()
+        });
+    let installer = SkillInstaller::new(downloader, url_parser).with_options(options);
+    let alias_service = AliasService::new(FileConfigStorage::new()?, url_parser);
+
+    installer.resolve_and_install(
+        skill_or_url,
+        destination,
+        &alias_service,
+        &default_skill_finder(&config)?,
+        &api_client(&config)?,
+        &ConsoleUserInteraction,
+    )?;
+    Ok(installer.install_plans())
+}
+
+/// Returns the skills in all configured markets whose name contains `query`
+pub fn search(query: &str) -> Result<Vec<SkillMatch>> {
+    let config = FileConfigStorage::new()?.load()?;
+    default_skill_finder(&config)?.find_matching(query, &SearchOptions::default())
+}
+
+/// Registers a GitHub repository URL as a market
+pub fn add_market(url: &str) -> Result<()> {
+    MarketService::new(FileMarketStorage::new()?, DefaultGitHubUrlParser).add_market(url, None)
+}
+
+fn default_skill_finder(
+    config: &Config,
+) -> Result<SkillFinder<FileMarketStorage, DefaultGitHubUrlParser, DefaultGitHubApiClient>> {
+    let market_service = MarketService::new(FileMarketStorage::new()?, DefaultGitHubUrlParser)
+        .with_default_market(config.use_default_market.unwrap_or(true));
+    let finder = SkillFinder::new(market_service, api_client(config)?);
+    Ok(match config.max_concurrency {
+        Some(max_concurrency) => finder.with_max_concurrency(max_concurrency),
+        None => finder,
+    })
+}
+
+fn api_client(config: &Config) -> Result<DefaultGitHubApiClient> {
+    Ok(DefaultGitHubApiClient::new()?
+        .with_retry(config.retry.unwrap_or_default())
+        .with_headers(parse_headers(&config.headers)?))
+}
@@ -1,43 +1,118 @@
-use anyhow::Result;
-use clap::Parser;
-
-mod github;
-mod installer;
-mod market;
-mod models;
-mod skill_finder;
-
-use clap::{Subcommand, ValueEnum};
-
-use github::{DefaultFileSystem, DefaultGitHubDownloader, DefaultGitHubUrlParser};
-use installer::{SkillInstaller, Target};
-use market::{DefaultGitHubApiClient, FileMarketStorage, MarketService};
-use skill_finder::{ConsoleUserInteraction, SkillFinder};
-
-#[derive(Debug, Clone, Copy, ValueEnum)]
-pub enum TargetType {
-    Codex,
-    Copilot,
-    Claude,
-    Cursor,
-}
+use anyhow::{Context, Result, anyhow};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-impl Target for TargetType {
-    fn as_str(&self) -> &'static str {
-        match self {
-            TargetType::Codex => "codex",
-            TargetType::Copilot => "copilot",
-            TargetType::Claude => "claude",
-            TargetType::Cursor => "cursor",
-        }
-    }
-}
+use skills::config::{AliasService, ConfigStorage, FileConfigStorage, default_config_dir};
+use skills::github::{
+    DefaultFileSystem, DefaultGitHubDownloader, DefaultGitHubUrlParser, DownloadOptions,
+    MANIFEST_FILE, OverwritePolicy, default_branch_fallback, parse_headers,
+};
+use skills::installer::{
+    InstallDestination, InstallOptions, InstallPlan, InstallRecord, ListedSkill, SkillInstaller,
+    TargetType, installed_skill_names, list_installed, print_stats, uninstall, uninstall_matching,
+};
+use skills::market::{
+    DefaultGitHubApiClient, FileMarketStorage, MAX_RATE_LIMIT_WAIT, MarketService,
+};
+use skills::skill_finder::{
+    ConsoleUserInteraction, DEFAULT_MAX_CONCURRENCY, MarketErrors, OutputFormat, SearchField,
+    SearchOptions, SkillFinder, is_glob, open_in_browser, parse_manifest_fields,
+};
 
 #[derive(Parser)]
 #[command(name = "skills")]
 #[command(version)]
 #[command(about = "A CLI for managing skills", long_about = None)]
 struct Cli {
+    #[arg(
+        long = "config",
+        global = true,
+        value_name = "DIR",
+        help = "Directory holding market.json and config.json [default: ~/.skills]"
+    )]
+    config: Option<PathBuf>,
+
+    #[arg(
+        long = "wait-for-rate-limit",
+        global = true,
+        help = "Wait for the GitHub API rate limit to reset (up to 15 minutes) instead of failing"
+    )]
+    wait_for_rate_limit: bool,
+
+    #[arg(
+        long = "wait-forever",
+        global = true,
+        requires = "wait_for_rate_limit",
+        help = "With --wait-for-rate-limit, wait however long the reset takes"
+    )]
+    wait_forever: bool,
+
+    #[arg(
+        long = "max-concurrency",
+        global = true,
+        value_name = "N",
+        help = "Maximum number of markets fetched at once [default: 8]"
+    )]
+    max_concurrency: Option<usize>,
+
+    #[arg(
+        long = "json",
+        global = true,
+        help = "Print errors as a JSON object on stderr, and install --dry-run previews and list output as JSON"
+    )]
+    json: bool,
+
+    #[arg(
+        long = "retry-attempts",
+        global = true,
+        value_name = "N",
+        help = "Tries per HTTP request before a connection or server error is fatal [default: 3]"
+    )]
+    retry_attempts: Option<u32>,
+
+    #[arg(
+        long = "retry-base-ms",
+        global = true,
+        value_name = "MS",
+        help = "Backoff before the first retry, doubled for each further one [default: 500]"
+    )]
+    retry_base_ms: Option<u64>,
+
+    #[arg(
+        long = "retry-max-ms",
+        global = true,
+        value_name = "MS",
+        help = "Longest backoff between two tries [default: 10000]"
+    )]
+    retry_max_ms: Option<u64>,
+
+    #[arg(
+        long = "retry-jitter",
+        global = true,
+        value_name = "BOOL",
+        help = "Sleep a random time up to the backoff instead of the full backoff [default: true]"
+    )]
+    retry_jitter: Option<bool>,
+
+    #[arg(
+        long = "temp-dir",
+        global = true,
+        value_name = "DIR",
+        env = "SKILLS_TMPDIR",
+        help = "Directory for download and extraction temp files [default: system temp dir]"
+    )]
+    temp_dir: Option<PathBuf>,
+
+    #[arg(
+        long = "header",
+        global = true,
+        value_name = "KEY: VALUE",
+        help = "Extra header sent with every GitHub request (repeatable)"
+    )]
+    headers: Vec<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -45,97 +120,1105 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     Install {
-        #[arg(help = "Skill name or GitHub repository URL")]
+        #[arg(help = "Skill name, GitHub repository URL, owner/repo, or owner/repo@branch:path")]
         skill_or_url: String,
 
         #[arg(
             short = 't',
             long = "type",
             value_enum,
-            help = "Target type for installation"
+            help = "Target type for installation [default: from .skills.toml]"
         )]
-        target: TargetType,
+        target: Option<TargetType>,
 
         #[arg(
             short = 'g',
             long = "global",
-            help = "Install globally to ~/.{type}/skills instead of ./.{type}/skills"
+            help = "Install globally under the home directory instead of the current project"
         )]
         global: bool,
+
+        #[arg(
+            long = "branch-fallback",
+            value_delimiter = ',',
+            help = "Branches to try in order when the requested branch does not exist [default: main,master]"
+        )]
+        branch_fallback: Option<Vec<String>>,
+
+        #[arg(
+            long = "dest-stdout",
+            conflicts_with = "global",
+            help = "Write the skill folder as a tarball to stdout instead of installing it"
+        )]
+        dest_stdout: bool,
+
+        #[arg(
+            long = "dir",
+            value_name = "PATH",
+            conflicts_with_all = ["global", "dest_stdout"],
+            help = "Install into this directory instead of the target's skills directory"
+        )]
+        dir: Option<PathBuf>,
+
+        #[arg(
+            long = "flat",
+            requires = "dir",
+            help = "Copy the skill's files directly into --dir without a skill-name subfolder"
+        )]
+        flat: bool,
+
+        #[arg(
+            long = "force",
+            help = "Allow a --flat install into a non-empty directory, or a skill requiring a newer CLI"
+        )]
+        force: bool,
+
+        #[arg(
+            long = "prune",
+            conflicts_with_all = ["dest_stdout", "manifest_only", "flat"],
+            help = "Remove installed files that no longer exist upstream"
+        )]
+        prune: bool,
+
+        #[arg(
+            long = "skip-large",
+            conflicts_with = "prune",
+            help = "Leave out files larger than --large-file-limit instead of only warning"
+        )]
+        skip_large: bool,
+
+        #[arg(
+            long = "large-file-limit",
+            value_name = "MiB",
+            default_value_t = 25,
+            help = "Size in MiB above which a file is reported as large"
+        )]
+        large_file_limit: u64,
+
+        #[arg(
+            long = "tree-api",
+            help = "Fetch only the skill's files via the git trees API instead of the whole repository archive"
+        )]
+        tree_api: bool,
+
+        #[arg(
+            long = "only",
+            value_name = "PATH",
+            conflicts_with_all = ["manifest_only", "prune"],
+            help = "Install only this file or directory of the skill folder (repeatable)"
+        )]
+        only: Vec<PathBuf>,
+
+        #[arg(
+            long = "no-subfolder-detect",
+            conflicts_with = "tree_api",
+            help = "Only look for the repository in the archive's {repo}-{branch} folder, not its single top-level folder"
+        )]
+        no_subfolder_detect: bool,
+
+        #[arg(
+            long = "strip-components",
+            value_name = "N",
+            default_value_t = 0,
+            conflicts_with = "manifest_only",
+            help = "Drop the first N directories from every file path inside the skill folder"
+        )]
+        strip_components: usize,
+
+        #[arg(
+            long = "overwrite-policy",
+            value_enum,
+            default_value_t = OverwritePolicy::Merge,
+            conflicts_with_all = ["dest_stdout", "manifest_only", "flat"],
+            help = "What to do when the skill folder already exists: merge over it, replace it, or skip the install"
+        )]
+        overwrite_policy: OverwritePolicy,
+
+        #[arg(
+            long = "read-only",
+            conflicts_with_all = ["dest_stdout", "manifest_only", "flat"],
+            help = "Make the installed files read-only to guard against accidental edits"
+        )]
+        read_only: bool,
+
+        #[arg(
+            long = "versioned",
+            conflicts_with = "flat",
+            help = "Install into <name>@<ref> to keep several versions side by side"
+        )]
+        versioned: bool,
+
+        #[arg(
+            long = "allow-repo-root",
+            help = "Allow a URL without a skill folder, installing the whole repository"
+        )]
+        allow_repo_root: bool,
+
+        #[arg(
+            long = "checksum",
+            conflicts_with_all = ["dest_stdout", "manifest_only"],
+            help = "Print a SHA-256 fingerprint of the installed files"
+        )]
+        checksum: bool,
+
+        #[arg(
+            long = "select",
+            value_name = "N",
+            help = "When several skills match, install the Nth one (as numbered by the prompt) without asking"
+        )]
+        select: Option<usize>,
+
+        #[arg(
+            long = "dry-run",
+            conflicts_with_all = ["dest_stdout", "report"],
+            help = "Show where the skill would be installed without downloading or writing anything"
+        )]
+        dry_run: bool,
+
+        #[arg(
+            long = "report",
+            value_name = "FILE",
+            conflicts_with = "dest_stdout",
+            help = "Write a JSON report of every install attempt to FILE, replacing it"
+        )]
+        report: Option<PathBuf>,
+
+        #[arg(long = "print-tree", help = "Print the installed file tree")]
+        print_tree: bool,
+
+        #[arg(
+            long = "max-depth",
+            requires = "print_tree",
+            help = "Maximum depth printed by --print-tree"
+        )]
+        max_depth: Option<usize>,
+
+        #[arg(
+            long = "manifest-only",
+            help = "Fetch only SKILL.md (printed to stdout with --dest-stdout)"
+        )]
+        manifest_only: bool,
     },
     Search {
         #[arg(help = "Search query to filter skills")]
         query: String,
+
+        #[arg(long, help = "Only match skills whose name equals the query")]
+        exact: bool,
+
+        #[arg(
+            long,
+            conflicts_with = "exact",
+            help = "Treat the query as a case-insensitive regular expression"
+        )]
+        regex: bool,
+
+        #[arg(
+            long = "manifest-check",
+            help = "Only show directories that contain a SKILL.md (one extra request per match)"
+        )]
+        manifest_check: bool,
+
+        #[arg(
+            long,
+            help = "Print only the number of matches, exiting non-zero when there are none"
+        )]
+        count: bool,
+
+        #[arg(
+            long = "market-only",
+            conflicts_with_all = ["exact", "regex", "manifest_check", "count"],
+            help = "List markets whose name or URL matches instead of skills"
+        )]
+        market_only: bool,
+
+        #[arg(
+            long = "markets",
+            value_name = "NAMES",
+            value_delimiter = ',',
+            conflicts_with = "market_only",
+            help = "Only search these markets (comma-separated owner/repo names)"
+        )]
+        markets: Vec<String>,
+
+        #[arg(
+            long = "exclude",
+            value_name = "PATTERN",
+            conflicts_with = "market_only",
+            help = "Leave out skills whose name contains PATTERN, or matches it as a glob such as 'test-*' (repeatable)"
+        )]
+        exclude: Vec<String>,
+
+        #[arg(
+            long = "manifest-field",
+            value_name = "KEY=VALUE",
+            conflicts_with = "market_only",
+            help = "Only keep skills whose SKILL.md frontmatter KEY contains VALUE, case-insensitively (repeatable)"
+        )]
+        manifest_field: Vec<String>,
+
+        #[arg(
+            long = "format",
+            value_enum,
+            default_value_t = OutputFormat::Text,
+            conflicts_with_all = ["count", "market_only"],
+            help = "Layout of the results"
+        )]
+        format: OutputFormat,
+
+        #[arg(
+            long = "fields",
+            value_enum,
+            value_delimiter = ',',
+            help = "Columns shown by --format table, in order [default: name,market,url]"
+        )]
+        fields: Option<Vec<SearchField>>,
+
+        #[arg(
+            long = "open",
+            conflicts_with_all = ["count", "market_only", "format"],
+            help = "Open the matching skill on GitHub in the browser, asking which one if several match"
+        )]
+        open: bool,
+
+        #[arg(
+            long = "count-per-market",
+            conflicts_with_all = ["count", "market_only", "format", "open", "new"],
+            help = "Print how many matching skills each market has (use \"\" to count all skills)"
+        )]
+        count_per_market: bool,
+
+        #[arg(
+            long = "new",
+            value_name = "DAYS",
+            num_args = 0..=1,
+            default_missing_value = "30",
+            conflicts_with_all = ["count", "market_only", "format", "open"],
+            help = "Sort by last change, newest first, and mark skills changed within DAYS days [default: 30]"
+        )]
+        new: Option<u64>,
+
+        #[arg(
+            long = "quiet-errors",
+            help = "Replace per-market fetch warnings with a single count"
+        )]
+        quiet_errors: bool,
+
+        #[arg(
+            long,
+            conflicts_with = "quiet_errors",
+            help = "Show the full cause of each market fetch failure"
+        )]
+        verbose: bool,
     },
     Market {
         #[command(subcommand)]
         action: MarketAction,
     },
+    Alias {
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+    /// Show how the installed copy of a skill differs from upstream
+    Diff {
+        #[arg(help = "Skill name, GitHub repository URL, or owner/repo@branch:path")]
+        skill_or_url: String,
+
+        #[arg(
+            short = 't',
+            long = "type",
+            value_enum,
+            required_unless_present = "dir",
+            help = "Target type the skill is installed for"
+        )]
+        target: Option<TargetType>,
+
+        #[arg(short = 'g', long = "global", help = "Compare the global install")]
+        global: bool,
+
+        #[arg(
+            long = "dir",
+            value_name = "PATH",
+            conflicts_with = "global",
+            help = "Directory the skill was installed into with install --dir"
+        )]
+        dir: Option<PathBuf>,
+    },
+    /// List the skills installed for a target, one per line
+    List {
+        #[arg(
+            short = 't',
+            long = "type",
+            value_enum,
+            help = "Target type to list the skills of"
+        )]
+        target: TargetType,
+
+        #[arg(short = 'g', long = "global", help = "List the global installs")]
+        global: bool,
+    },
+    /// Remove an installed skill
+    Uninstall {
+        #[arg(
+            help = "Name of the installed skill folder, e.g. pdf or pdf@v1.2 for versioned installs, or a glob such as \"pdf-*\" matching several"
+        )]
+        skill_name: String,
+
+        #[arg(
+            short = 't',
+            long = "type",
+            value_enum,
+            help = "Target type the skill is installed for"
+        )]
+        target: TargetType,
+
+        #[arg(short = 'g', long = "global", help = "Remove the global install")]
+        global: bool,
+
+        #[arg(
+            short = 'y',
+            long = "yes",
+            help = "Remove the skills a glob matches without asking for confirmation"
+        )]
+        yes: bool,
+    },
+    /// Check which GitHub account requests are made as
+    Auth {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+    /// Summarize installed skills per target with their disk usage
+    Stats,
+    /// Show the version with build details for bug reports
+    Version,
+    /// Generate a shell completion script
+    Completions {
+        #[arg(
+            value_enum,
+            help = "Shell to generate completions for [default: detected from $SHELL]"
+        )]
+        shell: Option<Shell>,
+
+        #[arg(
+            long = "install",
+            help = "Write the script to the shell's per-user completions directory"
+        )]
+        install: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuthAction {
+    /// Print the account the GitHub token belongs to and the remaining rate limit
+    Status,
+}
+
+#[derive(Subcommand)]
+enum AliasAction {
+    Add {
+        #[arg(help = "Alias name used in place of the skill name")]
+        name: String,
+
+        #[arg(help = "GitHub URL of the skill folder the alias points at")]
+        url: String,
+    },
+    List,
+    Remove {
+        #[arg(help = "Alias name to remove")]
+        name: String,
+    },
 }
 
 #[derive(Subcommand)]
 enum MarketAction {
     Add {
         #[arg(
-            help = "GitHub repository URL (e.g., https://github.com/owner/repo/tree/branch/path)"
+            required_unless_present = "from_org",
+            conflicts_with = "from_org",
+            help = "GitHub repository URLs (e.g., https://github.com/owner/repo/tree/branch/path)"
         )]
-        url: String,
+        urls: Vec<String>,
+
+        #[arg(
+            long = "from-org",
+            value_name = "ORG",
+            help = "Register every repository of a GitHub organization or user as a market"
+        )]
+        from_org: Option<String>,
+
+        #[arg(
+            long = "topic",
+            requires = "from_org",
+            help = "With --from-org, only repositories tagged with this topic"
+        )]
+        topic: Option<String>,
+
+        #[arg(
+            long = "dry-run",
+            help = "Print the resulting market list instead of saving it"
+        )]
+        dry_run: bool,
+
+        #[arg(
+            long = "branch",
+            conflicts_with = "from_org",
+            help = "Branch to use for URLs that do not name one [default: main]"
+        )]
+        branch: Option<String>,
+
+        #[arg(
+            long = "probe-depth",
+            value_name = "N",
+            help = "Search up to N folder levels below each URL for where the skills live"
+        )]
+        probe_depth: Option<usize>,
+
+        #[arg(
+            long = "validate-skills",
+            help = "Only save a market whose folder contains at least one skill"
+        )]
+        validate_skills: bool,
+    },
+    /// Delete a market from market.json
+    Remove {
+        #[arg(help = "Market name (owner/repo) or URL")]
+        name: String,
+
+        #[arg(
+            long = "dry-run",
+            help = "Print the resulting market list instead of saving it"
+        )]
+        dry_run: bool,
+    },
+    /// Re-probe every market for moved skill folders and renamed default branches
+    Refresh {
+        #[arg(
+            long = "probe-depth",
+            value_name = "N",
+            default_value_t = 2,
+            help = "How many folder levels below the repository root to search for moved skills"
+        )]
+        probe_depth: usize,
+
+        #[arg(
+            long = "dry-run",
+            help = "Print the resulting market list instead of saving it"
+        )]
+        dry_run: bool,
+    },
+    /// Merge markets that point at the same repository folder through different URLs
+    Dedupe {
+        #[arg(
+            long = "dry-run",
+            help = "Print the resulting market list instead of saving it"
+        )]
+        dry_run: bool,
+    },
+    /// Change where a market sits in the search order
+    Move {
+        #[arg(help = "Market name (owner/repo) or URL")]
+        name: String,
+
+        #[arg(help = "New position, starting at 1 for the first market searched")]
+        position: usize,
     },
     Search {
         #[arg(help = "Search query to filter skills")]
         query: String,
     },
+    /// Include a disabled market in searches again
+    Enable {
+        #[arg(help = "Market name (owner/repo) or URL")]
+        name: String,
+    },
+    /// Leave a market out of searches without removing it
+    Disable {
+        #[arg(help = "Market name (owner/repo) or URL")]
+        name: String,
+    },
+    Export,
+    Import {
+        #[arg(help = "Path to a JSON file produced by 'skills market export'")]
+        file: PathBuf,
+
+        #[arg(long, help = "Replace the configured markets instead of merging")]
+        replace: bool,
+    },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    if !cli.json {
+        return run(cli);
+    }
+
+    if let Err(e) = run(cli) {
+        eprintln!("{}", error_json(&e));
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Renders an error as `{"error": {"kind", "message", "context"}}`, where `context` holds
+/// the causes beneath the top-level message
+fn error_json(error: &anyhow::Error) -> serde_json::Value {
+    let root = error.root_cause();
+    let kind = if root.is::<reqwest::Error>() {
+        "network"
+    } else if root.is::<std::io::Error>() {
+        "io"
+    } else if root.is::<serde_json::Error>() {
+        "parse"
+    } else {
+        "error"
+    };
+
+    serde_json::json!({
+        "error": {
+            "kind": kind,
+            "message": error.to_string(),
+            "context": error.chain().skip(1).map(|cause| cause.to_string()).collect::<Vec<_>>(),
+        }
+    })
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let config_dir = match &cli.config {
+        Some(dir) => dir.clone(),
+        None => default_config_dir()?,
+    };
+
+    let config_storage = FileConfigStorage::in_dir(&config_dir);
+    let config = config_storage.load()?;
+
+    let branch_fallback = match &cli.command {
+        Commands::Install {
+            branch_fallback: Some(branches),
+            ..
+        } => branches.clone(),
+        _ => config
+            .branch_fallback
+            .unwrap_or_else(default_branch_fallback),
+    };
+
+    let mut retry = config.retry.unwrap_or_default();
+    retry.attempts = cli.retry_attempts.unwrap_or(retry.attempts);
+    retry.base_ms = cli.retry_base_ms.unwrap_or(retry.base_ms);
+    retry.max_ms = cli.retry_max_ms.unwrap_or(retry.max_ms);
+    retry.jitter = cli.retry_jitter.unwrap_or(retry.jitter);
+
+    let headers = parse_headers(&[config.headers.clone(), cli.headers.clone()].concat())?;
+
     // Initialize dependencies (Dependency Injection)
     let url_parser = DefaultGitHubUrlParser;
     let file_system = DefaultFileSystem;
-    let downloader = DefaultGitHubDownloader::new(file_system);
-    let storage = FileMarketStorage::new()?;
-    let api_client = DefaultGitHubApiClient::new()?;
+    let download_options = match &cli.command {
+        Commands::Install {
+            prune,
+            skip_large,
+            large_file_limit,
+            tree_api,
+            read_only,
+            strip_components,
+            overwrite_policy,
+            no_subfolder_detect,
+            only,
+            ..
+        } => DownloadOptions {
+            branch_fallback,
+            prune: *prune,
+            large_file_limit: large_file_limit * 1024 * 1024,
+            skip_large: *skip_large,
+            tree_api: *tree_api,
+            read_only: *read_only,
+            retry,
+            headers: headers.clone(),
+            temp_dir: cli.temp_dir.clone(),
+            strip_components: *strip_components,
+            overwrite: *overwrite_policy,
+            subfolder_detect: !*no_subfolder_detect,
+            only: only.clone(),
+        },
+        _ => DownloadOptions {
+            branch_fallback,
+            retry,
+            headers: headers.clone(),
+            temp_dir: cli.temp_dir.clone(),
+            ..DownloadOptions::default()
+        },
+    };
+    let downloader = DefaultGitHubDownloader::new(file_system).with_options(download_options);
+    let storage = FileMarketStorage::in_dir(&config_dir);
+    let rate_limit_wait = match (cli.wait_for_rate_limit, cli.wait_forever) {
+        (_, true) => Some(Duration::MAX),
+        (true, false) => Some(MAX_RATE_LIMIT_WAIT),
+        (false, false) => None,
+    };
+    // Every command talking to the API shares the same rate limit, retry and header settings
+    let new_api_client = || -> Result<DefaultGitHubApiClient> {
+        Ok(DefaultGitHubApiClient::new()?
+            .with_rate_limit_wait(rate_limit_wait)
+            .with_retry(retry)
+            .with_headers(headers.clone()))
+    };
+    let api_client = new_api_client()?;
     let user_interaction = ConsoleUserInteraction;
 
     // Create services with injected dependencies
-    let market_service = MarketService::new(storage, url_parser);
-    let skill_finder = SkillFinder::new(market_service, api_client);
+    let use_default_market = config.use_default_market.unwrap_or(true);
+    let market_service =
+        MarketService::new(storage, url_parser).with_default_market(use_default_market);
+    let max_concurrency = cli
+        .max_concurrency
+        .or(config.max_concurrency)
+        .unwrap_or(DEFAULT_MAX_CONCURRENCY);
+    let skill_finder =
+        SkillFinder::new(market_service, api_client).with_max_concurrency(max_concurrency);
     let installer = SkillInstaller::new(downloader, url_parser);
+    let alias_service = AliasService::new(config_storage, url_parser);
+    let market_admin = MarketService::new(FileMarketStorage::in_dir(&config_dir), url_parser)
+        .with_default_market(use_default_market);
 
     match cli.command {
         Commands::Install {
             skill_or_url,
             target,
             global,
+            dest_stdout,
+            dir,
+            flat,
+            force,
+            manifest_only,
+            print_tree,
+            max_depth,
+            versioned,
+            checksum,
+            allow_repo_root,
+            report,
+            select,
+            dry_run,
+            ..
         } => {
-            if skill_or_url.starts_with("http") {
-                installer.install_from_url(&skill_or_url, &target, global)?;
-            } else {
-                installer.install_from_market(
+            let installer = installer.with_options(InstallOptions {
+                print_tree,
+                max_depth,
+                versioned,
+                force,
+                checksum,
+                allow_repo_root,
+                record: report.is_some(),
+                select,
+                dry_run,
+                manifest_only,
+            });
+
+            let install = || -> Result<()> {
+                // Chosen before resolving the skill so a missing target fails without network access
+                let destination = if dest_stdout {
+                    InstallDestination::Stdout
+                } else if let Some(dir) = dir {
+                    InstallDestination::Dir { dir, flat }
+                } else if let Some(target) = target {
+                    InstallDestination::Target { target, global }
+                } else if global {
+                    return Err(anyhow!("--type is required for global installation"));
+                } else {
+                    InstallDestination::project(&std::env::current_dir()?)?
+                };
+
+                installer.resolve_and_install(
                     &skill_or_url,
-                    &target,
-                    global,
+                    &destination,
+                    &alias_service,
                     &skill_finder,
+                    &new_api_client()?,
                     &user_interaction,
-                )?;
+                )
+            };
+
+            let result = install();
+            if let Some(report) = report {
+                let mut records = installer.install_records();
+                if let Err(e) = &result
+                    && records.is_empty()
+                {
+                    records.push(InstallRecord::failed(&skill_or_url, e));
+                }
+                write_install_report(&report, &records)?;
+            }
+            result?;
+
+            if dry_run {
+                print_install_plans(&installer.install_plans(), cli.json)?;
             }
         }
-        Commands::Search { query } => {
-            skill_finder.search(&query)?;
+        Commands::Search {
+            query,
+            exact,
+            regex,
+            manifest_check,
+            count,
+            market_only,
+            format,
+            fields,
+            open,
+            new,
+            count_per_market,
+            quiet_errors,
+            verbose,
+            markets,
+            exclude,
+            manifest_field,
+        } => {
+            if fields.is_some() && !matches!(format, OutputFormat::Table) {
+                return Err(anyhow!("--fields only applies to --format table"));
+            }
+            let options = SearchOptions {
+                exact,
+                regex,
+                manifest_check,
+                format,
+                fields: fields.unwrap_or_default(),
+                market_errors: if quiet_errors {
+                    MarketErrors::Summary
+                } else if verbose {
+                    MarketErrors::Verbose
+                } else {
+                    MarketErrors::Warn
+                },
+                new_within: new.map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+                markets,
+                exclude,
+                manifest_fields: parse_manifest_fields(&manifest_field)?,
+            };
+
+            if market_only {
+                skill_finder.search_markets(&query)?;
+            } else if open {
+                let skill = skill_finder.find_one(&query, &options, &user_interaction)?;
+                open_in_browser(&skill.url)?;
+            } else if count_per_market {
+                let counts = skill_finder.count_per_market(&query, &options)?;
+                let width = counts.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+                for (name, count) in counts {
+                    println!("{:<width$}  {}", name, count);
+                }
+            } else if count {
+                let matches = skill_finder.find_matching(&query, &options)?;
+                println!("{}", matches.len());
+                if matches.is_empty() {
+                    std::process::exit(1);
+                }
+            } else {
+                skill_finder.search(&query, &options)?;
+            }
         }
         Commands::Market { action } => match action {
-            MarketAction::Add { url } => {
-                let storage = FileMarketStorage::new()?;
-                let url_parser = DefaultGitHubUrlParser;
-                let market_service = MarketService::new(storage, url_parser);
-                market_service.add_market(&url)?;
+            MarketAction::Add {
+                urls,
+                from_org,
+                topic,
+                dry_run,
+                branch,
+                probe_depth,
+                validate_skills,
+            } => {
+                let api_client = new_api_client()?;
+                let market_admin = market_admin.with_dry_run(dry_run);
+                match from_org {
+                    Some(org) => market_admin.add_org_markets(
+                        &org,
+                        topic.as_deref(),
+                        &api_client,
+                        probe_depth,
+                        validate_skills,
+                    )?,
+                    None => {
+                        let probe = probe_depth.map(|depth| (&api_client, depth));
+                        let validate = validate_skills.then_some(&api_client);
+                        market_admin.add_markets(&urls, branch.as_deref(), probe, validate)?;
+                    }
+                }
+                if dry_run {
+                    market_admin.print_dry_run()?;
+                }
+            }
+            MarketAction::Remove { name, dry_run } => {
+                let market_admin = market_admin.with_dry_run(dry_run);
+                market_admin.remove_market(&name)?;
+                if dry_run {
+                    market_admin.print_dry_run()?;
+                }
+            }
+            MarketAction::Refresh {
+                probe_depth,
+                dry_run,
+            } => {
+                let api_client = new_api_client()?;
+                let market_admin = market_admin.with_dry_run(dry_run);
+                market_admin.refresh_markets(&api_client, probe_depth)?;
+                if dry_run {
+                    market_admin.print_dry_run()?;
+                }
+            }
+            MarketAction::Dedupe { dry_run } => {
+                let market_admin = market_admin.with_dry_run(dry_run);
+                market_admin.dedupe_markets()?;
+                if dry_run {
+                    market_admin.print_dry_run()?;
+                }
+            }
+            MarketAction::Move { name, position } => {
+                market_admin.move_market(&name, position)?;
             }
             MarketAction::Search { query } => {
-                skill_finder.search(&query)?;
+                skill_finder.search(&query, &SearchOptions::default())?;
+            }
+            MarketAction::Enable { name } => market_admin.set_market_enabled(&name, true)?,
+            MarketAction::Disable { name } => market_admin.set_market_enabled(&name, false)?,
+            MarketAction::Export => {
+                println!("{}", market_admin.export_markets()?);
+            }
+            MarketAction::Import { file, replace } => {
+                market_admin.import_markets(&file, replace)?;
+            }
+        },
+        Commands::Diff {
+            skill_or_url,
+            target,
+            global,
+            dir,
+        } => {
+            let skill = match alias_service.resolve_source(&skill_or_url)? {
+                Some(url) => installer.resolve_url(&url)?,
+                None => installer.resolve_from_market(
+                    &skill_or_url,
+                    &skill_finder,
+                    &user_interaction,
+                )?,
+            };
+
+            let installed = match (dir, target) {
+                (Some(dir), _) => dir.join(installer.folder_name(&skill)),
+                (None, Some(target)) => installer.installed_path(&skill, &target, global)?,
+                (None, None) => return Err(anyhow!("--type or --dir is required")),
+            };
+
+            let changed = installer.diff(&skill, &installed, &mut std::io::stdout().lock())?;
+            if changed == 0 {
+                eprintln!(
+                    "No differences between {} and upstream",
+                    installed.display()
+                );
+            } else {
+                eprintln!("{} file(s) differ from upstream", changed);
+            }
+        }
+        Commands::List { target, global } => {
+            let (skills_dir, names) = installed_skill_names(&target, global)?;
+            if cli.json {
+                let skills = names
+                    .iter()
+                    .map(|name| ListedSkill::new(name, &skills_dir, &target))
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string_pretty(&skills)?);
+            } else if names.is_empty() {
+                eprintln!("No skills installed in {}", skills_dir.display());
+            } else {
+                for name in &names {
+                    println!("{}", name);
+                }
+                eprintln!(
+                    "{} skill(s) installed in {}",
+                    names.len(),
+                    skills_dir.display()
+                );
+            }
+        }
+        Commands::Uninstall {
+            skill_name,
+            target,
+            global,
+            yes,
+        } => {
+            if is_glob(&skill_name) {
+                let removed = uninstall_matching(
+                    &skill_name,
+                    &target,
+                    global,
+                    yes,
+                    &DefaultFileSystem,
+                    &user_interaction,
+                )?;
+                if removed.is_empty() {
+                    eprintln!("Nothing removed");
+                }
+                for path in &removed {
+                    println!("Removed {}", path.display());
+                }
+            } else {
+                let removed = uninstall(&skill_name, &target, global, &DefaultFileSystem)?;
+                println!("Removed {}", removed.display());
+            }
+        }
+        Commands::Auth {
+            action: AuthAction::Status,
+        } => {
+            let api_client = new_api_client()?;
+            match api_client.authenticated_login()? {
+                Some(login) => println!("Authenticated as {}", login),
+                None => println!(
+                    "No token in GITHUB_TOKEN or GH_TOKEN; requests are anonymous (60 per hour)"
+                ),
+            }
+
+            let rate = api_client.rate_limit()?;
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            println!(
+                "Rate limit: {} of {} requests left this hour, resets in {} minute(s)",
+                rate.remaining,
+                rate.limit,
+                rate.reset.saturating_sub(now).div_ceil(60)
+            );
+        }
+        Commands::Stats => print_stats(&list_installed()?),
+        Commands::Version => print_version(),
+        Commands::Completions { shell, install } => {
+            let shell = match shell {
+                Some(shell) => shell,
+                None => Shell::from_env().ok_or_else(|| {
+                    anyhow!("Could not detect the shell from $SHELL; pass it explicitly")
+                })?,
+            };
+
+            if install {
+                install_completions(shell)?;
+            } else {
+                clap_complete::generate(
+                    shell,
+                    &mut Cli::command(),
+                    "skills",
+                    &mut std::io::stdout(),
+                );
             }
+        }
+        Commands::Alias { action } => match action {
+            AliasAction::Add { name, url } => alias_service.add_alias(&name, &url)?,
+            AliasAction::List => alias_service.list_aliases()?,
+            AliasAction::Remove { name } => alias_service.remove_alias(&name)?,
         },
     }
 
     Ok(())
 }
+
+/// Writes the completion script where the shell picks up per-user completions
+fn install_completions(shell: Shell) -> Result<()> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+    let (path, reload) = match shell {
+        Shell::Bash => (
+            home.join(".local/share/bash-completion/completions/skills"),
+            "Open a new shell to load them",
+        ),
+        Shell::Zsh => (
+            home.join(".zfunc/_skills"),
+            "Add `fpath=(~/.zfunc $fpath)` before `compinit` in ~/.zshrc, then open a new shell",
+        ),
+        Shell::Fish => (
+            home.join(".config/fish/completions/skills.fish"),
+            "Open a new shell to load them",
+        ),
+        other => {
+            return Err(anyhow!(
+                "--install is not supported for {}; redirect `skills completions {}` to a file instead",
+                other,
+                other
+            ));
+        }
+    };
+
+    write_completions(shell, &path)?;
+    println!("Installed {} completions to {}", shell, path.display());
+    println!("{}", reload);
+    Ok(())
+}
+
+/// Writes the install records as a JSON array, replacing any previous report
+fn write_install_report(path: &Path, records: &[InstallRecord]) -> Result<()> {
+    let json = serde_json::to_string_pretty(records)?;
+    std::fs::write(path, json)
+        .with_context(|| format!("Failed to write install report {}", path.display()))?;
+    eprintln!("Wrote install report to {}", path.display());
+    Ok(())
+}
+
+/// Prints the previewed installs, as a JSON array with `json`
+fn print_install_plans(plans: &[InstallPlan], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(plans)?);
+        return Ok(());
+    }
+
+    for plan in plans {
+        if plan.would_skip {
+            println!(
+                "Would skip {}: {} already exists (--overwrite-policy skip)",
+                plan.skill,
+                plan.destination.display()
+            );
+            continue;
+        }
+        let what = if plan.manifest_only {
+            format!("{} of {}", MANIFEST_FILE, plan.skill)
+        } else {
+            plan.skill.clone()
+        };
+        let overwrite = if plan.would_overwrite {
+            ", overwriting existing files"
+        } else {
+            ""
+        };
+        println!(
+            "Would install {} into {}{}",
+            what,
+            plan.destination.display(),
+            overwrite
+        );
+        println!("  from {}", plan.url);
+        if let Some(commit) = &plan.commit {
+            println!("  at commit {}", commit);
+        }
+    }
+    println!("\nDry run, nothing was downloaded or written");
+    Ok(())
+}
+
+fn write_completions(shell: Shell, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::File::create(path)?;
+    clap_complete::generate(shell, &mut Cli::command(), "skills", &mut file);
+    Ok(())
+}
+
+fn print_version() {
+    let unknown = "unknown";
+    println!("skills {}", env!("CARGO_PKG_VERSION"));
+    println!(
+        "commit: {}",
+        option_env!("VERGEN_GIT_SHA").unwrap_or(unknown)
+    );
+    println!(
+        "build date: {}",
+        option_env!("VERGEN_BUILD_DATE").unwrap_or(unknown)
+    );
+    println!(
+        "rustc: {}",
+        option_env!("VERGEN_RUSTC_SEMVER").unwrap_or(unknown)
+    );
+}
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+/// Parses the `---`-delimited YAML frontmatter at the top of a SKILL.md into its
+/// top-level `key: value` pairs. Block scalars are joined into one line; nested
+/// values and lists are not interpreted.
+pub fn parse_frontmatter(content: &str) -> BTreeMap<String, String> {
+    let mut fields = BTreeMap::new();
+    let mut lines = content.lines();
+
+    if lines.next().map(str::trim) != Some("---") {
+        return fields;
+    }
+
+    let mut block_key: Option<String> = None;
+    for line in lines {
+        if line.trim() == "---" {
+            break;
+        }
+
+        // Indented lines continue a block scalar (`key: >` or `key: |`) or belong to a
+        // nested value of the previous key
+        if line.starts_with(char::is_whitespace) {
+            if let Some(key) = &block_key
+                && let Some(value) = fields.get_mut(key)
+            {
+                if !value.is_empty() {
+                    value.push(' ');
+                }
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+
+        block_key = None;
+        if let Some((key, value)) = line.split_once(':') {
+            let key = key.trim().to_string();
+            let value = value.trim();
+
+            if matches!(value, "|" | ">" | "|-" | ">-") {
+                block_key = Some(key.clone());
+                fields.insert(key, String::new());
+            } else {
+                let value = value.trim_matches('"').trim_matches('\'');
+                fields.insert(key, value.to_string());
+            }
+        }
+    }
+
+    fields
+}
+
+/// Returns the `min_cli_version` declared in a SKILL.md frontmatter, if any
+pub fn min_cli_version(content: &str) -> Option<String> {
+    parse_frontmatter(content)
+        .remove("min_cli_version")
+        .filter(|version| !version.is_empty())
+}
+
+/// Returns the `description` declared in a SKILL.md frontmatter, if any
+pub fn description(content: &str) -> Option<String> {
+    parse_frontmatter(content)
+        .remove("description")
+        .filter(|description| !description.is_empty())
+}
@@ -1,32 +1,80 @@
 use anyhow::{Context, Result, anyhow};
+use reqwest::header::HeaderMap;
+use std::cell::RefCell;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::github::GitHubUrlParser;
-use crate::models::{GitHubContent, MarketEntry};
+use crate::config::default_config_dir;
+use crate::github::{GitHubUrlParser, MANIFEST_FILE};
+use crate::models::{
+    AuthenticatedUser, GitHubContent, GitHubRepo, MarketEntry, OrgRepository, RateLimit,
+    RateLimitResponse, Release, RepoCommit,
+};
+use crate::retry::RetryPolicy;
+
+/// Name of the market that is always searched
+pub const DEFAULT_MARKET_NAME: &str = "anthropics/skills";
+
+/// URL of the market that is always searched
+pub const DEFAULT_MARKET_URL: &str = "https://github.com/anthropics/skills/tree/main/skills";
 
 /// Trait for accessing market configuration storage
 pub trait MarketStorage {
     fn load(&self) -> Result<Vec<MarketEntry>>;
     fn save(&self, markets: &[MarketEntry]) -> Result<()>;
+
+    /// Loads read-only markets contributed by other tools, merged after `load` when searching
+    fn load_sources(&self) -> Result<Vec<MarketEntry>> {
+        Ok(Vec::new())
+    }
 }
 
-/// Trait for interacting with GitHub API
-pub trait GitHubApiClient {
+/// Trait for interacting with GitHub API. Clients are shared across the threads that
+/// fetch markets concurrently.
+pub trait GitHubApiClient: Sync {
     fn get_directory_contents(&self, repo: &str, path: &str) -> Result<Vec<GitHubContent>>;
+    fn get_file_contents(&self, repo: &str, path: &str) -> Result<String>;
+
+    /// Lists the repositories owned by an organization, or by a user of that name
+    fn list_org_repositories(&self, org: &str) -> Result<Vec<OrgRepository>>;
+
+    /// Returns the repository's default branch
+    fn default_branch(&self, repo: &str) -> Result<String>;
+
+    /// Whether `branch` exists in the repository
+    fn branch_exists(&self, repo: &str, branch: &str) -> Result<bool>;
+
+    /// Tag of the repository's latest release, or `None` when it has no releases
+    fn latest_release_tag(&self, repo: &str) -> Result<Option<String>>;
+
+    /// Time of the latest commit touching `path`, or `None` when the client cannot tell
+    fn last_commit_time(&self, _repo: &str, _path: &str) -> Result<Option<SystemTime>> {
+        Ok(None)
+    }
+
+    /// SHA of the commit `git_ref` points at, or `None` when the client cannot tell
+    fn commit_sha(&self, _repo: &str, _git_ref: &str) -> Result<Option<String>> {
+        Ok(None)
+    }
 }
 
 /// Default implementation of MarketStorage using file system
 pub struct FileMarketStorage {
     config_path: PathBuf,
+    sources_dir: PathBuf,
 }
 
 impl FileMarketStorage {
     pub fn new() -> Result<Self> {
-        let home_dir =
-            dirs::home_dir().ok_or_else(|| anyhow!("Could not determine home directory"))?;
-        let config_path = home_dir.join(".skills").join("market.json");
-        Ok(Self { config_path })
+        Ok(Self::in_dir(&default_config_dir()?))
+    }
+
+    pub fn in_dir(dir: &Path) -> Self {
+        Self {
+            config_path: dir.join("market.json"),
+            sources_dir: dir.join("sources.d"),
+        }
     }
 }
 
@@ -47,7 +95,7 @@ impl MarketStorage for FileMarketStorage {
 
     fn save(&self, markets: &[MarketEntry]) -> Result<()> {
         if let Some(parent) = self.config_path.parent() {
-            fs::create_dir_all(parent).context("Failed to create .skills directory")?;
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
         }
 
         let json = serde_json::to_string_pretty(markets).context("Failed to serialize markets")?;
@@ -56,41 +104,498 @@ impl MarketStorage for FileMarketStorage {
 
         Ok(())
     }
+
+    fn load_sources(&self) -> Result<Vec<MarketEntry>> {
+        if !self.sources_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = fs::read_dir(&self.sources_dir)
+            .context("Failed to read sources.d")?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect::<Vec<_>>();
+        files.sort();
+
+        let mut markets = Vec::new();
+        for file in files {
+            let parsed = fs::read_to_string(&file)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_str::<Vec<MarketEntry>>(&content)?));
+
+            match parsed {
+                Ok(entries) => markets.extend(entries),
+                Err(e) => eprintln!("Warning: Skipping {}: {}", file.display(), e),
+            }
+        }
+
+        Ok(markets)
+    }
 }
 
+/// Longest rate limit reset that is waited out unless waiting forever is requested
+pub const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// Shortest wait before a rate-limited request is sent again, so a `Retry-After: 0` or a
+/// reset time already past does not resend in a tight loop
+const MIN_RATE_LIMIT_DELAY: Duration = Duration::from_secs(1);
+
+/// Number of times one request waits out a rate limit before the response is returned
+const MAX_RATE_LIMIT_WAITS: u32 = 5;
+
 /// Default implementation of GitHubApiClient
 pub struct DefaultGitHubApiClient {
     client: reqwest::blocking::Client,
+    token: Option<String>,
+    rate_limit_wait: Option<Duration>,
+    retry: RetryPolicy,
+    headers: HeaderMap,
 }
 
 impl DefaultGitHubApiClient {
     pub fn new() -> Result<Self> {
+        // Large directory listings compress well; responses are decoded transparently
         let client = reqwest::blocking::Client::builder()
             .user_agent("skills-cli")
+            .gzip(true)
+            .brotli(true)
             .build()?;
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            token: github_token(),
+            rate_limit_wait: None,
+            retry: RetryPolicy::default(),
+            headers: HeaderMap::new(),
+        })
+    }
+
+    /// Sends `headers` with every request, e.g. for a corporate gateway
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Sleeps until the rate limit resets instead of failing, as long as the reset is at
+    /// most `max_wait` away
+    pub fn with_rate_limit_wait(mut self, max_wait: Option<Duration>) -> Self {
+        self.rate_limit_wait = max_wait;
+        self
+    }
+
+    /// Retries connection errors and server errors as `retry` says
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Login of the user the configured token belongs to, or `None` without a token
+    pub fn authenticated_login(&self) -> Result<Option<String>> {
+        if self.token.is_none() {
+            return Ok(None);
+        }
+
+        let response = self.send("https://api.github.com/user", None, "GitHub")?;
+        if !response.status().is_success() {
+            return Err(self.describe_error(response, "the authenticated user", ""));
+        }
+
+        let user: AuthenticatedUser = response
+            .json()
+            .context("Failed to parse GitHub API response")?;
+        Ok(Some(user.login))
+    }
+
+    /// Core API rate limit of the configured token, or of anonymous access without one.
+    /// Looking it up does not count against the limit.
+    pub fn rate_limit(&self) -> Result<RateLimit> {
+        let response = self.send("https://api.github.com/rate_limit", None, "GitHub")?;
+        if !response.status().is_success() {
+            return Err(self.describe_error(response, "the rate limit", ""));
+        }
+
+        let status: RateLimitResponse = response
+            .json()
+            .context("Failed to parse GitHub API response")?;
+        Ok(status.rate)
+    }
+
+    /// Sends a GET request, retrying transient failures and waiting out rate limits when
+    /// configured to
+    fn send(
+        &self,
+        api_url: &str,
+        accept: Option<&str>,
+        repo: &str,
+    ) -> Result<reqwest::blocking::Response> {
+        let mut waits = 0;
+        loop {
+            let request = || {
+                let mut request = self.client.get(api_url).headers(self.headers.clone());
+                if let Some(accept) = accept {
+                    request = request.header(reqwest::header::ACCEPT, accept);
+                }
+                if let Some(token) = &self.token {
+                    request = request.bearer_auth(token);
+                }
+                request
+            };
+
+            let response = self
+                .retry
+                .send(request)
+                .context(format!("Failed to fetch from {}", repo))?;
+
+            match self.rate_limit_delay(&response) {
+                Some(_) if waits == MAX_RATE_LIMIT_WAITS => {
+                    eprintln!(
+                        "Warning: Still rate limited after waiting {} times; giving up",
+                        MAX_RATE_LIMIT_WAITS
+                    );
+                    return Ok(response);
+                }
+                Some(delay) => {
+                    waits += 1;
+                    wait_with_countdown(delay.max(MIN_RATE_LIMIT_DELAY));
+                }
+                None => return Ok(response),
+            }
+        }
+    }
+
+    /// How long to sleep before retrying a rate-limited response, or `None` to hand the
+    /// response back as is
+    fn rate_limit_delay(&self, response: &reqwest::blocking::Response) -> Option<Duration> {
+        let max_wait = self.rate_limit_wait?;
+
+        let status = response.status();
+        let headers = response.headers();
+        if status != reqwest::StatusCode::FORBIDDEN
+            && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+        {
+            return None;
+        }
+
+        // The secondary (abuse) limit names its own wait instead of a reset time
+        if let Some(delay) = retry_after(headers) {
+            if delay > max_wait {
+                eprintln!(
+                    "Warning: GitHub asks to wait {} second(s), longer than the {} minute maximum wait; pass --wait-forever to wait anyway",
+                    delay.as_secs(),
+                    max_wait.as_secs() / 60
+                );
+                return None;
+            }
+            return Some(delay);
+        }
+
+        if headers
+            .get("x-ratelimit-remaining")
+            .is_none_or(|v| v.as_bytes() != b"0")
+        {
+            return None;
+        }
+
+        let reset = headers
+            .get("x-ratelimit-reset")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        // One extra second so the retry does not land just before the reset
+        let delay = Duration::from_secs(reset.saturating_sub(now) + 1);
+
+        if delay > max_wait {
+            eprintln!(
+                "Warning: Rate limit resets in {} minute(s), longer than the {} minute maximum wait; pass --wait-forever to wait anyway",
+                delay.as_secs().div_ceil(60),
+                max_wait.as_secs() / 60
+            );
+            return None;
+        }
+
+        Some(delay)
+    }
+
+    /// Turns an unsuccessful API response into an error that tells "not found" apart from
+    /// "forbidden", naming the token scope needed for private repositories
+    fn describe_error(
+        &self,
+        response: reqwest::blocking::Response,
+        repo: &str,
+        path: &str,
+    ) -> anyhow::Error {
+        let status = response.status();
+        let headers = response.headers().clone();
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+
+        let body_message = response
+            .json::<serde_json::Value>()
+            .ok()
+            .and_then(|body| body.get("message")?.as_str().map(|m| format!(" ({})", m)))
+            .unwrap_or_default();
+
+        // GitHub only reports scopes for classic tokens; fine-grained tokens omit the header
+        let missing_repo_scope = header("x-oauth-scopes")
+            .map(|scopes| !scopes.split(',').any(|scope| scope.trim() == "repo"))
+            .unwrap_or(false);
+
+        let location = if path.is_empty() {
+            repo.to_string()
+        } else {
+            format!("{}/{}", repo, path)
+        };
+
+        let secondary_wait = retry_after(&headers);
+
+        match status {
+            reqwest::StatusCode::FORBIDDEN | reqwest::StatusCode::TOO_MANY_REQUESTS
+                if secondary_wait.is_some() =>
+            {
+                anyhow!(
+                    "GitHub's secondary rate limit was hit while fetching {}; wait {} second(s) before retrying, or pass --wait-for-rate-limit to wait automatically",
+                    location,
+                    secondary_wait.unwrap_or_default().as_secs()
+                )
+            }
+            reqwest::StatusCode::FORBIDDEN
+                if header("x-ratelimit-remaining").as_deref() == Some("0") =>
+            {
+                anyhow!("GitHub API rate limit exceeded while fetching {}", location)
+            }
+            reqwest::StatusCode::UNAUTHORIZED if self.token.is_some() => anyhow!(
+                "GitHub rejected the token in GITHUB_TOKEN/GH_TOKEN while fetching {}{}; check that it is valid and not expired",
+                location,
+                body_message
+            ),
+            reqwest::StatusCode::FORBIDDEN if self.token.is_none() => anyhow!(
+                "Access to {} is forbidden{}. Set GITHUB_TOKEN to a token with the `repo` scope",
+                location,
+                body_message
+            ),
+            reqwest::StatusCode::FORBIDDEN if missing_repo_scope => anyhow!(
+                "Access to {} is forbidden: the GitHub token lacks the `repo` scope required for private repositories",
+                location
+            ),
+            reqwest::StatusCode::FORBIDDEN => {
+                anyhow!("Access to {} is forbidden{}", location, body_message)
+            }
+            // GitHub answers 404 rather than 403 for private repositories the caller cannot see
+            reqwest::StatusCode::NOT_FOUND if self.token.is_none() => anyhow!(
+                "{} not found. If it is a private repository, set GITHUB_TOKEN to a token with the `repo` scope",
+                location
+            ),
+            reqwest::StatusCode::NOT_FOUND if missing_repo_scope => anyhow!(
+                "{} not found, or the GitHub token lacks the `repo` scope required for private repositories",
+                location
+            ),
+            reqwest::StatusCode::NOT_FOUND => anyhow!("{} not found", location),
+            _ => anyhow!("HTTP error: {}", status),
+        }
+    }
+}
+
+/// Wait requested by a `Retry-After` header given in seconds, as GitHub sends with its
+/// secondary rate limit
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Sleeps for `delay`, printing the remaining seconds to stderr
+fn wait_with_countdown(delay: Duration) {
+    for remaining in (1..=delay.as_secs()).rev() {
+        eprint!("\rRate limit reached, resuming in {}s   ", remaining);
+        std::thread::sleep(Duration::from_secs(1));
     }
+    eprintln!("\rRate limit reset, resuming            ");
+}
+
+/// Returns the GitHub token from `GITHUB_TOKEN` or `GH_TOKEN`, if set
+pub fn github_token() -> Option<String> {
+    ["GITHUB_TOKEN", "GH_TOKEN"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|token| !token.trim().is_empty())
 }
 
 impl GitHubApiClient for DefaultGitHubApiClient {
     fn get_directory_contents(&self, repo: &str, path: &str) -> Result<Vec<GitHubContent>> {
         let api_url = format!("https://api.github.com/repos/{}/contents/{}", repo, path);
 
-        let response = self
-            .client
-            .get(&api_url)
-            .send()
-            .context(format!("Failed to fetch from {}", repo))?;
+        let response = self.send(&api_url, None, repo)?;
+
+        if !response.status().is_success() {
+            return Err(self.describe_error(response, repo, path));
+        }
+
+        // A path naming a file is answered with that file's object instead of a listing
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Contents {
+            Listing(Vec<GitHubContent>),
+            Single(GitHubContent),
+        }
+
+        match response
+            .json()
+            .context("Failed to parse GitHub API response")?
+        {
+            Contents::Listing(contents) => Ok(contents),
+            Contents::Single(item) => Err(anyhow!(
+                "'{}' in {} is a {}, not a directory; check the market URL's path",
+                item.path,
+                repo,
+                item.item_type
+            )),
+        }
+    }
+
+    fn get_file_contents(&self, repo: &str, path: &str) -> Result<String> {
+        let api_url = format!("https://api.github.com/repos/{}/contents/{}", repo, path);
+
+        let response = self.send(&api_url, Some("application/vnd.github.raw"), repo)?;
+
+        if !response.status().is_success() {
+            return Err(self.describe_error(response, repo, path));
+        }
+
+        response.text().context("Failed to read file contents")
+    }
+
+    fn list_org_repositories(&self, org: &str) -> Result<Vec<OrgRepository>> {
+        const PAGE_SIZE: usize = 100;
+
+        let mut repositories = Vec::new();
+        // Personal accounts are not organizations, so fall back to the user endpoint
+        let mut owner_kind = "orgs";
+        let mut page = 1;
+
+        loop {
+            let api_url = format!(
+                "https://api.github.com/{}/{}/repos?per_page={}&page={}",
+                owner_kind, org, PAGE_SIZE, page
+            );
+            let response = self.send(&api_url, None, org)?;
+
+            if response.status() == reqwest::StatusCode::NOT_FOUND && owner_kind == "orgs" {
+                owner_kind = "users";
+                continue;
+            }
+
+            if !response.status().is_success() {
+                return Err(self.describe_error(response, org, ""));
+            }
+
+            let batch: Vec<OrgRepository> = response
+                .json()
+                .context("Failed to parse GitHub API response")?;
+            let last_page = batch.len() < PAGE_SIZE;
+            repositories.extend(batch);
+
+            if last_page {
+                return Ok(repositories);
+            }
+            page += 1;
+        }
+    }
+
+    fn default_branch(&self, repo: &str) -> Result<String> {
+        let api_url = format!("https://api.github.com/repos/{}", repo);
+
+        let response = self.send(&api_url, None, repo)?;
 
         if !response.status().is_success() {
-            return Err(anyhow!("HTTP error: {}", response.status()));
+            return Err(self.describe_error(response, repo, ""));
         }
 
-        let contents: Vec<GitHubContent> = response
+        let repository: OrgRepository = response
             .json()
             .context("Failed to parse GitHub API response")?;
+        Ok(repository.default_branch)
+    }
+
+    fn branch_exists(&self, repo: &str, branch: &str) -> Result<bool> {
+        let api_url = format!("https://api.github.com/repos/{}/branches/{}", repo, branch);
+
+        let response = self.send(&api_url, None, repo)?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            return Err(self.describe_error(response, repo, ""));
+        }
+        Ok(true)
+    }
+
+    fn latest_release_tag(&self, repo: &str) -> Result<Option<String>> {
+        let api_url = format!("https://api.github.com/repos/{}/releases/latest", repo);
+
+        let response = self.send(&api_url, None, repo)?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(self.describe_error(response, repo, ""));
+        }
+
+        let release: Release = response
+            .json()
+            .context("Failed to parse GitHub API response")?;
+        Ok(Some(release.tag_name))
+    }
+
+    fn last_commit_time(&self, repo: &str, path: &str) -> Result<Option<SystemTime>> {
+        let api_url = format!(
+            "https://api.github.com/repos/{}/commits?path={}&per_page=1",
+            repo, path
+        );
+
+        let response = self.send(&api_url, None, repo)?;
 
-        Ok(contents)
+        if !response.status().is_success() {
+            return Err(self.describe_error(response, repo, path));
+        }
+
+        let commits: Vec<RepoCommit> = response
+            .json()
+            .context("Failed to parse GitHub API response")?;
+
+        Ok(commits
+            .first()
+            .and_then(|commit| parse_timestamp(&commit.commit.committer.date)))
+    }
+
+    fn commit_sha(&self, repo: &str, git_ref: &str) -> Result<Option<String>> {
+        let api_url = format!("https://api.github.com/repos/{}/commits/{}", repo, git_ref);
+
+        let response = self.send(&api_url, None, repo)?;
+
+        // 422 is GitHub's answer for a ref that names no commit
+        if response.status() == reqwest::StatusCode::NOT_FOUND
+            || response.status() == reqwest::StatusCode::UNPROCESSABLE_ENTITY
+        {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(self.describe_error(response, repo, ""));
+        }
+
+        let commit: RepoCommit = response
+            .json()
+            .context("Failed to parse GitHub API response")?;
+        Ok(Some(commit.sha))
     }
 }
 
@@ -98,6 +603,10 @@ impl GitHubApiClient for DefaultGitHubApiClient {
 pub struct MarketService<S: MarketStorage, U: GitHubUrlParser> {
     storage: S,
     url_parser: U,
+    use_default_market: bool,
+    dry_run: bool,
+    /// Markets that would have been saved during a dry run
+    pending: RefCell<Option<Vec<MarketEntry>>>,
 }
 
 impl<S: MarketStorage, U: GitHubUrlParser> MarketService<S, U> {
@@ -105,15 +614,70 @@ impl<S: MarketStorage, U: GitHubUrlParser> MarketService<S, U> {
         Self {
             storage,
             url_parser,
+            use_default_market: true,
+            dry_run: false,
+            pending: RefCell::new(None),
         }
     }
 
-    pub fn add_market(&self, url: &str) -> Result<()> {
-        let mut markets = self.storage.load()?;
+    /// Whether the built-in market is searched alongside the configured ones
+    pub fn with_default_market(mut self, use_default_market: bool) -> Self {
+        self.use_default_market = use_default_market;
+        self
+    }
+
+    /// Keeps changes in memory instead of writing market.json; `print_dry_run` shows the
+    /// result
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Prints the market list a dry run would have saved
+    pub fn print_dry_run(&self) -> Result<()> {
+        match &*self.pending.borrow() {
+            Some(markets) => println!(
+                "\nDry run, market.json not written. It would contain:\n{}",
+                serde_json::to_string_pretty(markets)?
+            ),
+            None => println!("\nDry run, market.json would not change"),
+        }
+        Ok(())
+    }
+
+    /// Configured markets, including changes made earlier in a dry run
+    fn load(&self) -> Result<Vec<MarketEntry>> {
+        match &*self.pending.borrow() {
+            Some(markets) => Ok(markets.clone()),
+            None => self.storage.load(),
+        }
+    }
+
+    fn save(&self, markets: &[MarketEntry]) -> Result<()> {
+        if self.dry_run {
+            *self.pending.borrow_mut() = Some(markets.to_vec());
+            return Ok(());
+        }
+        self.storage.save(markets)
+    }
+
+    /// Adds a market; `branch` is searched instead of `main` when the URL names no branch
+    pub fn add_market(&self, url: &str, branch: Option<&str>) -> Result<()> {
+        let mut markets = self.load()?;
 
         let name = self.extract_repo_name(url)?;
 
-        if markets.iter().any(|m| m.url == url) {
+        if branch.is_some() && url.contains("/tree/") {
+            return Err(anyhow!(
+                "--branch only applies to URLs without /tree/<branch>; {} already names one",
+                url
+            ));
+        }
+
+        if markets
+            .iter()
+            .any(|m| m.url == url && m.branch.as_deref() == branch)
+        {
             println!("Market '{}' is already added", name);
             return Ok(());
         }
@@ -121,25 +685,303 @@ impl<S: MarketStorage, U: GitHubUrlParser> MarketService<S, U> {
         markets.push(MarketEntry {
             name,
             url: url.to_string(),
+            branch: branch.map(str::to_string),
+            enabled: true,
         });
 
-        self.storage.save(&markets)?;
+        self.save(&markets)?;
+
+        if self.dry_run {
+            println!("Would add market: {}", url);
+        } else {
+            println!("Successfully added market: {}", url);
+        }
+        Ok(())
+    }
+
+    /// Removes every configured market whose name or URL is `name`
+    pub fn remove_market(&self, name: &str) -> Result<()> {
+        let mut markets = self.load()?;
+        let before = markets.len();
+        markets.retain(|m| m.name != name && m.url != name);
+
+        if markets.len() == before {
+            if name == DEFAULT_MARKET_NAME {
+                return Err(anyhow!(
+                    "'{}' is built in; set \"use_default_market\": false in config.json to stop searching it",
+                    name
+                ));
+            }
+            return Err(anyhow!("No market named '{}' in market.json", name));
+        }
+
+        self.save(&markets)?;
+
+        let verb = if self.dry_run {
+            "Would remove"
+        } else {
+            "Removed"
+        };
+        println!(
+            "{} {} market(s) matching '{}'",
+            verb,
+            before - markets.len(),
+            name
+        );
+        Ok(())
+    }
+
+    /// Adds each URL in turn, continuing past failures, and errors at the end if any failed.
+    /// With `probe`, each URL is first narrowed down to where its skills live; with `validate`,
+    /// a URL is only saved when its folder holds at least one skill.
+    pub fn add_markets<A: GitHubApiClient>(
+        &self,
+        urls: &[String],
+        branch: Option<&str>,
+        probe: Option<(&A, usize)>,
+        validate: Option<&A>,
+    ) -> Result<()> {
+        let mut failed = 0;
+
+        for url in urls {
+            let added = match probe {
+                // The probed URL spells out the branch, so it is not stored separately
+                Some((api_client, depth)) => self
+                    .probe_market_url(url, branch, depth, api_client)
+                    .and_then(|probed| self.add_market(&probed, None)),
+                None => match validate {
+                    Some(api_client) => self
+                        .validate_market_url(url, branch, api_client)
+                        .and_then(|_| self.add_market(url, branch)),
+                    None => self.add_market(url, branch),
+                },
+            };
+
+            if let Err(e) = added {
+                eprintln!("Failed to add market {}: {:#}", url, e);
+                failed += 1;
+            }
+        }
+
+        if urls.len() > 1 {
+            println!(
+                "\n{} of {} market(s) processed successfully",
+                urls.len() - failed,
+                urls.len()
+            );
+        }
+
+        if failed > 0 {
+            return Err(anyhow!("{} market(s) could not be added", failed));
+        }
+
+        Ok(())
+    }
+
+    /// Registers each non-archived repository of `org` (optionally only those tagged with
+    /// `topic`) as a market
+    pub fn add_org_markets<A: GitHubApiClient>(
+        &self,
+        org: &str,
+        topic: Option<&str>,
+        api_client: &A,
+        probe_depth: Option<usize>,
+        validate: bool,
+    ) -> Result<()> {
+        let urls = api_client
+            .list_org_repositories(org)?
+            .into_iter()
+            .filter(|repo| !repo.archived)
+            .filter(|repo| topic.is_none_or(|topic| repo.topics.iter().any(|t| t == topic)))
+            .map(|repo| {
+                format!(
+                    "https://github.com/{}/tree/{}",
+                    repo.full_name, repo.default_branch
+                )
+            })
+            .collect::<Vec<_>>();
 
-        println!("Successfully added market: {}", url);
+        if urls.is_empty() {
+            return Err(match topic {
+                Some(topic) => anyhow!("No repositories in {} have the topic '{}'", org, topic),
+                None => anyhow!("No repositories found in {}", org),
+            });
+        }
+
+        self.add_markets(
+            &urls,
+            None,
+            probe_depth.map(|depth| (api_client, depth)),
+            validate.then_some(api_client),
+        )
+    }
+
+    /// Counts the skills directly below the market URL, erroring when there are none
+    pub fn validate_market_url<A: GitHubApiClient>(
+        &self,
+        url: &str,
+        branch: Option<&str>,
+        api_client: &A,
+    ) -> Result<usize> {
+        let parsed = self.parse_market_url(url, branch)?;
+        let repo_path = format!("{}/{}", parsed.owner, parsed.repo);
+
+        let (skills, _) = count_skill_folders(&repo_path, &parsed.path, api_client)?;
+        if skills == 0 {
+            return Err(anyhow!(
+                "No skills ({} folders) found at {}; not saving it",
+                MANIFEST_FILE,
+                url
+            ));
+        }
+
+        println!("Found {} skill(s) at {}", skills, url);
+        Ok(skills)
+    }
+
+    /// Walks up to `depth` levels below the market URL and returns the URL of the folder
+    /// holding the most skills (directories containing SKILL.md), preferring shallower folders
+    pub fn probe_market_url<A: GitHubApiClient>(
+        &self,
+        url: &str,
+        branch: Option<&str>,
+        depth: usize,
+        api_client: &A,
+    ) -> Result<String> {
+        let parsed = self.parse_market_url(url, branch)?;
+        let repo_path = format!("{}/{}", parsed.owner, parsed.repo);
+
+        // Breadth-first so that on equal skill counts the shallowest folder wins
+        let mut best: Option<(String, usize)> = None;
+        let mut level = vec![parsed.path.clone()];
+        for _ in 0..=depth {
+            let mut next_level = Vec::new();
+
+            for path in level {
+                let (skills, others) = count_skill_folders(&repo_path, &path, api_client)?;
+                next_level.extend(others);
+
+                if skills > best.as_ref().map_or(0, |(_, count)| *count) {
+                    best = Some((path, skills));
+                }
+            }
+
+            level = next_level;
+        }
+
+        let (path, skills) = best.ok_or_else(|| {
+            anyhow!(
+                "No skills ({} folders) found within {} level(s) of {}",
+                MANIFEST_FILE,
+                depth,
+                url
+            )
+        })?;
+
+        let probed = format!(
+            "https://github.com/{}/tree/{}/{}",
+            repo_path, parsed.branch, path
+        );
+        let probed = probed.trim_end_matches('/').to_string();
+        println!("Discovered {} skill(s) at {}", skills, probed);
+
+        Ok(probed)
+    }
+
+    /// Re-checks every market in market.json against GitHub, replacing vanished branches with
+    /// the repository's default branch and re-probing, up to `depth` levels below the
+    /// repository root, paths that no longer hold skills
+    pub fn refresh_markets<A: GitHubApiClient>(&self, api_client: &A, depth: usize) -> Result<()> {
+        let mut markets = self.load()?;
+        let mut changed = 0;
+        let mut failed = 0;
+
+        for market in markets.iter_mut() {
+            // Rewriting would store the expanded values, which may be secrets
+            if market.url.contains("${") {
+                println!(
+                    "Skipping {}: its URL uses environment variables",
+                    market.url
+                );
+                continue;
+            }
+
+            match self.refresh_market(market, api_client, depth) {
+                Ok(Some(url)) => {
+                    println!("Updated {}: {} -> {}", market.name, market.url, url);
+                    market.url = url;
+                    // The refreshed URL spells out the branch
+                    market.branch = None;
+                    changed += 1;
+                }
+                Ok(None) => println!("Unchanged: {}", market.url),
+                Err(e) => {
+                    eprintln!("Warning: Failed to refresh {}: {:#}", market.url, e);
+                    failed += 1;
+                }
+            }
+        }
+
+        if changed > 0 {
+            self.save(&markets)?;
+        }
+        println!("\n{} of {} market(s) updated", changed, markets.len());
+
+        if failed > 0 {
+            return Err(anyhow!("{} market(s) could not be refreshed", failed));
+        }
         Ok(())
     }
 
+    /// Returns the market's new URL, or `None` when it is still current
+    fn refresh_market<A: GitHubApiClient>(
+        &self,
+        market: &MarketEntry,
+        api_client: &A,
+        depth: usize,
+    ) -> Result<Option<String>> {
+        let mut parsed = self.parse_market_url(&market.url, market.branch.as_deref())?;
+        let repo_path = format!("{}/{}", parsed.owner, parsed.repo);
+
+        if !api_client.branch_exists(&repo_path, &parsed.branch)? {
+            let default_branch = api_client.default_branch(&repo_path)?;
+            println!(
+                "Branch '{}' of {} is gone; using default branch '{}'",
+                parsed.branch, repo_path, default_branch
+            );
+            parsed.branch = default_branch;
+        }
+
+        // A path that disappeared fails to list, which also calls for probing
+        let skills = count_skill_folders(&repo_path, &parsed.path, api_client)
+            .map_or(0, |(skills, _)| skills);
+        let url = if skills > 0 {
+            parsed.tree_url()
+        } else {
+            println!(
+                "No skills left at {}; probing {}",
+                parsed.tree_url(),
+                repo_path
+            );
+            let root = GitHubRepo {
+                path: String::new(),
+                ..parsed
+            };
+            self.probe_market_url(&root.tree_url(), None, depth, api_client)?
+        };
+
+        if url == self.canonical_url(&market.url, market.branch.as_deref())? {
+            Ok(None)
+        } else {
+            Ok(Some(url))
+        }
+    }
+
     pub fn get_repositories(&self) -> Result<Vec<(String, String, String, String)>> {
-        let mut repositories = vec![(
-            "anthropics/skills".to_string(),
-            "skills".to_string(),
-            "https://github.com/anthropics/skills/tree/main".to_string(),
-            "anthropics/skills".to_string(),
-        )];
+        let mut repositories: Vec<(String, String, String, String)> = Vec::new();
 
-        let markets = self.storage.load()?;
-        for market in markets {
-            let parsed = self.url_parser.parse(&market.url)?;
+        for market in self.all_markets()?.into_iter().filter(|m| m.enabled) {
+            let parsed = self.parse_market_url(&market.url, market.branch.as_deref())?;
             let repo_path = format!("{}/{}", parsed.owner, parsed.repo);
             let base_url = format!("https://github.com/{}/tree/{}", repo_path, parsed.branch);
 
@@ -156,8 +998,339 @@ impl<S: MarketStorage, U: GitHubUrlParser> MarketService<S, U> {
         Ok(repositories)
     }
 
+    /// Like `get_repositories`, limited to the markets named in `names` unless it is empty.
+    /// Fails when a name matches no enabled market.
+    pub fn get_repositories_in(
+        &self,
+        names: &[String],
+    ) -> Result<Vec<(String, String, String, String)>> {
+        let repositories = self.get_repositories()?;
+        if names.is_empty() {
+            return Ok(repositories);
+        }
+
+        for name in names {
+            if !repositories.iter().any(|(_, _, _, market)| market == name) {
+                return Err(anyhow!(
+                    "No enabled market named '{}'; see `skills market search \"\"` for the configured markets",
+                    name
+                ));
+            }
+        }
+
+        Ok(repositories
+            .into_iter()
+            .filter(|(_, _, _, market)| names.contains(market))
+            .collect())
+    }
+
+    /// Enables or disables every market in `market.json` whose name or URL is `name`
+    pub fn set_market_enabled(&self, name: &str, enabled: bool) -> Result<()> {
+        let mut markets = self.load()?;
+
+        let mut changed = 0;
+        let mut matched = false;
+        for market in markets
+            .iter_mut()
+            .filter(|m| m.name == name || m.url == name)
+        {
+            matched = true;
+            if market.enabled != enabled {
+                market.enabled = enabled;
+                changed += 1;
+            }
+        }
+
+        if !matched {
+            if name == DEFAULT_MARKET_NAME {
+                return Err(anyhow!(
+                    "'{}' is built in; set \"use_default_market\": false in config.json to disable it",
+                    name
+                ));
+            }
+            return Err(anyhow!("No market named '{}' in market.json", name));
+        }
+
+        let state = if enabled { "enabled" } else { "disabled" };
+        if changed == 0 {
+            println!("Market '{}' is already {}", name, state);
+            return Ok(());
+        }
+
+        self.save(&markets)?;
+
+        println!("Market '{}' {}", name, state);
+        Ok(())
+    }
+
+    /// Moves the market named `name` (or with that URL) to `position`, counted from 1, in
+    /// market.json, which is the order markets are searched and listed in
+    pub fn move_market(&self, name: &str, position: usize) -> Result<()> {
+        let mut markets = self.load()?;
+
+        let matching = markets
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.name == name || m.url == name)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let from = match matching[..] {
+            [] => return Err(anyhow!("No market named '{}' in market.json", name)),
+            [from] => from,
+            _ => {
+                return Err(anyhow!(
+                    "{} markets are named '{}'; pass the URL of the one to move",
+                    matching.len(),
+                    name
+                ));
+            }
+        };
+
+        if position == 0 || position > markets.len() {
+            return Err(anyhow!(
+                "Position {} is out of range; market.json has {} market(s)",
+                position,
+                markets.len()
+            ));
+        }
+
+        let market = markets.remove(from);
+        markets.insert(position - 1, market);
+        self.save(&markets)?;
+
+        println!("Moved market '{}' to position {}", name, position);
+        Ok(())
+    }
+
+    /// Rewrites every URL in `market.json` to its canonical `/tree/<branch>` form and merges
+    /// entries that then point at the same folder. The first entry keeps its name and place,
+    /// and stays enabled when any of its duplicates was. URLs with `${VAR}` references are
+    /// left as written, so expanded values never end up in the file.
+    pub fn dedupe_markets(&self) -> Result<()> {
+        let markets = self.load()?;
+        let before = markets.len();
+
+        let mut deduped: Vec<MarketEntry> = Vec::new();
+        let mut keys: Vec<String> = Vec::new();
+        let mut rewritten = 0;
+        for mut market in markets {
+            let key = if market.url.contains("${") {
+                market.url.clone()
+            } else {
+                let canonical = self
+                    .canonical_url(&market.url, market.branch.as_deref())
+                    .with_context(|| format!("Failed to parse market URL {}", market.url))?;
+                if canonical != market.url || market.branch.is_some() {
+                    market.url = canonical.clone();
+                    market.branch = None;
+                    rewritten += 1;
+                }
+                canonical
+            };
+
+            match keys.iter().position(|k| *k == key) {
+                Some(i) => {
+                    println!(
+                        "Merging market '{}' into '{}' ({})",
+                        market.name, deduped[i].name, key
+                    );
+                    deduped[i].enabled |= market.enabled;
+                }
+                None => {
+                    keys.push(key);
+                    deduped.push(market);
+                }
+            }
+        }
+
+        let merged = before - deduped.len();
+        if merged > 0 || rewritten > 0 {
+            self.save(&deduped)?;
+        }
+
+        let verb = if self.dry_run {
+            "Would merge"
+        } else {
+            "Merged"
+        };
+        println!(
+            "{} {} duplicate market(s); {} URL(s) rewritten to canonical form",
+            verb, merged, rewritten
+        );
+        Ok(())
+    }
+
+    /// Returns the configured markets whose name or URL contains `query`, without
+    /// contacting GitHub
+    pub fn find_markets(&self, query: &str) -> Result<Vec<MarketEntry>> {
+        let query_lower = query.to_lowercase();
+
+        Ok(self
+            .all_markets()?
+            .into_iter()
+            .filter(|m| {
+                m.name.to_lowercase().contains(&query_lower)
+                    || m.url.to_lowercase().contains(&query_lower)
+            })
+            .collect())
+    }
+
+    /// The built-in market (unless disabled) followed by `market.json` and `sources.d` entries
+    fn all_markets(&self) -> Result<Vec<MarketEntry>> {
+        let mut markets = Vec::new();
+        if self.use_default_market {
+            markets.push(MarketEntry {
+                name: DEFAULT_MARKET_NAME.to_string(),
+                url: DEFAULT_MARKET_URL.to_string(),
+                branch: None,
+                enabled: true,
+            });
+        }
+        markets.extend(self.storage.load()?);
+        markets.extend(self.storage.load_sources()?);
+        Ok(markets)
+    }
+
+    pub fn export_markets(&self) -> Result<String> {
+        let markets = self.storage.load()?;
+        serde_json::to_string_pretty(&markets).context("Failed to serialize markets")
+    }
+
+    pub fn import_markets(&self, path: &Path, replace: bool) -> Result<()> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let imported: Vec<MarketEntry> = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        let mut markets = if replace { Vec::new() } else { self.load()? };
+
+        let mut known = markets
+            .iter()
+            .map(|m| self.canonical_url(&m.url, m.branch.as_deref()))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut added = 0;
+        for entry in imported {
+            let canonical = self.canonical_url(&entry.url, entry.branch.as_deref())?;
+            if known.contains(&canonical) {
+                println!("Skipping duplicate market: {}", entry.url);
+                continue;
+            }
+
+            known.push(canonical);
+            markets.push(entry);
+            added += 1;
+        }
+
+        self.save(&markets)?;
+
+        println!(
+            "Imported {} market(s), {} total configured",
+            added,
+            markets.len()
+        );
+        Ok(())
+    }
+
+    /// Normalizes a market URL so that equivalent spellings compare equal
+    fn canonical_url(&self, url: &str, branch: Option<&str>) -> Result<String> {
+        Ok(self.parse_market_url(url, branch)?.tree_url())
+    }
+
+    /// Parses a market URL after expanding `${VAR}` references, using the market's own
+    /// `branch` when the URL names none
+    fn parse_market_url(&self, url: &str, branch: Option<&str>) -> Result<GitHubRepo> {
+        let url = &expand_env(url)?;
+        let mut parsed = self.url_parser.parse(url)?;
+        if let Some(branch) = branch
+            && !url.contains("/tree/")
+        {
+            parsed.branch = branch.to_string();
+        }
+        Ok(parsed)
+    }
+
     fn extract_repo_name(&self, url: &str) -> Result<String> {
-        let parsed = self.url_parser.parse(url)?;
+        let parsed = self.parse_market_url(url, None)?;
         Ok(format!("{}/{}", parsed.owner, parsed.repo))
     }
 }
+
+/// Counts the subfolders of `path` that contain a SKILL.md, also returning the other subfolders
+fn count_skill_folders<A: GitHubApiClient>(
+    repo_path: &str,
+    path: &str,
+    api_client: &A,
+) -> Result<(usize, Vec<String>)> {
+    let subdirs = api_client
+        .get_directory_contents(repo_path, path)?
+        .into_iter()
+        .filter(|item| item.item_type == "dir")
+        .map(|item| item.path);
+
+    let mut skills = 0;
+    let mut others = Vec::new();
+    for subdir in subdirs {
+        let contents = api_client.get_directory_contents(repo_path, &subdir)?;
+        if contents
+            .iter()
+            .any(|item| item.item_type == "file" && item.name == MANIFEST_FILE)
+        {
+            skills += 1;
+        } else {
+            others.push(subdir);
+        }
+    }
+
+    Ok((skills, others))
+}
+
+/// Parses a UTC timestamp as GitHub writes it (`2024-05-01T12:34:56Z`)
+fn parse_timestamp(timestamp: &str) -> Option<SystemTime> {
+    let (date, time) = timestamp.strip_suffix('Z')?.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.splitn(3, ':').map(str::parse::<u64>);
+    let (hours, minutes, seconds) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar, counting years from March
+    // so that leap days fall at the end
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = u64::try_from(era * 146_097 + day_of_era - 719_468).ok()?;
+
+    let secs = days * 86_400 + hours * 3600 + minutes * 60 + seconds;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Replaces each `${VAR}` in a stored market URL with the environment variable's value,
+/// so hosts and tokens can stay out of market.json
+fn expand_env(url: &str) -> Result<String> {
+    let mut expanded = String::with_capacity(url.len());
+    let mut rest = url;
+
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| anyhow!("Unterminated ${{...}} in market URL {}", url))?;
+
+        let name = &after[..end];
+        let value = std::env::var(name).map_err(|_| {
+            anyhow!(
+                "Market URL {} references ${{{}}}, which is not set",
+                url,
+                name
+            )
+        })?;
+        expanded.push_str(&value);
+        rest = &after[end + 1..];
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
@@ -1,11 +1,34 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::SystemTime;
 
-#[derive(Debug)]
+use crate::retry::RetryPolicy;
+
+#[derive(Debug, Clone)]
 pub struct GitHubRepo {
     pub owner: String,
     pub repo: String,
     pub branch: String,
     pub path: String,
+    /// `branch` names a tag (such as a release) rather than a branch
+    pub is_tag: bool,
+    /// `branch` was named explicitly, so no fallback branch is tried in its place
+    pub pinned: bool,
+}
+
+impl GitHubRepo {
+    /// Browsable GitHub URL of the folder
+    pub fn tree_url(&self) -> String {
+        let base = format!(
+            "https://github.com/{}/{}/tree/{}",
+            self.owner, self.repo, self.branch
+        );
+        if self.path.is_empty() {
+            base
+        } else {
+            format!("{}/{}", base, self.path)
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -16,10 +39,97 @@ pub struct GitHubContent {
     pub path: String,
 }
 
+/// Repository as listed by the org/user repositories API
+#[derive(Debug, Deserialize)]
+pub struct OrgRepository {
+    /// Repository as `owner/repo`
+    pub full_name: String,
+    pub default_branch: String,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+/// Response of the git trees API
+#[derive(Debug, Deserialize)]
+pub struct GitTree {
+    pub tree: Vec<GitTreeEntry>,
+    /// Set when the repository is too large for GitHub to list in one response
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Response of the latest release API, reduced to its tag
+#[derive(Debug, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+}
+
+/// Entry of the commits API, reduced to its SHA and commit time
+#[derive(Debug, Deserialize)]
+pub struct RepoCommit {
+    pub sha: String,
+    pub commit: CommitDetails,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitDetails {
+    pub committer: CommitSignature,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitSignature {
+    /// ISO 8601 timestamp such as `2024-05-01T12:34:56Z`
+    pub date: String,
+}
+
+/// Response of the authenticated user API, reduced to the login
+#[derive(Debug, Deserialize)]
+pub struct AuthenticatedUser {
+    pub login: String,
+}
+
+/// Response of the rate limit API, reduced to the core limit
+#[derive(Debug, Deserialize)]
+pub struct RateLimitResponse {
+    pub rate: RateLimit,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RateLimit {
+    /// Requests allowed per hour
+    pub limit: u64,
+    pub remaining: u64,
+    /// Unix time at which `remaining` is reset to `limit`
+    pub reset: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitTreeEntry {
+    pub path: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketEntry {
     pub name: String,
     pub url: String,
+    /// Branch searched when the URL has no `/tree/<branch>` part
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Disabled markets stay configured but are left out of searches and installs
+    #[serde(default = "enabled_by_default", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+fn is_enabled(enabled: &bool) -> bool {
+    *enabled
 }
 
 #[derive(Debug, Clone)]
@@ -27,4 +137,38 @@ pub struct SkillMatch {
     pub name: String,
     pub url: String,
     pub market_name: String,
+    /// Repository as `owner/repo`
+    pub repo: String,
+    /// Path of the skill folder inside the repository
+    pub path: String,
+    pub description: Option<String>,
+    /// Time of the latest commit touching the skill folder, when looked up
+    pub updated: Option<SystemTime>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedSkill {
+    pub repo: GitHubRepo,
+    pub name: String,
+    pub market_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch_fallback: Option<Vec<String>>,
+    /// Set to `false` to never search the built-in `anthropics/skills` market
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_default_market: Option<bool>,
+    /// Maximum number of markets fetched at the same time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+    /// Retrying of transient HTTP failures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    /// Extra `Key: Value` headers sent with every GitHub request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<String>,
 }
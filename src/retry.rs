@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How transient HTTP failures (connection errors, timeouts and 5xx responses) are retried
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryPolicy {
+    /// Total number of tries, including the first
+    pub attempts: u32,
+    /// Backoff before the first retry in milliseconds, doubled for every further retry
+    pub base_ms: u64,
+    /// Upper bound on a single backoff in milliseconds
+    pub max_ms: u64,
+    /// Sleep a random time between zero and the backoff instead of the backoff itself, so
+    /// parallel requests do not retry in lockstep
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            base_ms: 500,
+            max_ms: 10_000,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Sends the request built by `request`, building and sending it again after a backoff
+    /// while it fails transiently and attempts remain
+    pub fn send<F>(&self, request: F) -> reqwest::Result<reqwest::blocking::Response>
+    where
+        F: Fn() -> reqwest::blocking::RequestBuilder,
+    {
+        let mut attempt = 1;
+        loop {
+            let result = request().send();
+
+            let reason = match &result {
+                Ok(response) if response.status().is_server_error() => {
+                    format!("HTTP {}", response.status())
+                }
+                Err(e) if e.is_connect() || e.is_timeout() => e.to_string(),
+                _ => return result,
+            };
+            if attempt >= self.attempts {
+                return result;
+            }
+
+            let delay = self.backoff(attempt);
+            eprintln!(
+                "Warning: Request failed ({}), retrying in {:.1}s (attempt {} of {})",
+                reason,
+                delay.as_secs_f64(),
+                attempt + 1,
+                self.attempts
+            );
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    /// Time to wait after the `attempt`-th failed try
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_ms
+            .saturating_mul(1u64 << (attempt - 1).min(32))
+            .min(self.max_ms);
+
+        let ms = if self.jitter {
+            random_u64() % (exponential + 1)
+        } else {
+            exponential
+        };
+        Duration::from_millis(ms)
+    }
+}
+
+/// A random number from the standard library's randomly seeded hasher, which is plenty for
+/// spreading out retries
+fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
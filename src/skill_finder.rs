@@ -1,14 +1,121 @@
-use anyhow::Result;
-use std::io::{self, Write};
+use anyhow::{Context, Result, anyhow};
+use clap::ValueEnum;
+use globset::{GlobBuilder, GlobMatcher};
+use rayon::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::time::{Duration, SystemTime};
 
-use crate::github::GitHubUrlParser;
+use crate::github::{GitHubUrlParser, MANIFEST_FILE};
+use crate::installer::format_age;
+use crate::manifest;
 use crate::market::{GitHubApiClient, MarketService, MarketStorage};
-use crate::models::{GitHubContent, SkillMatch};
+use crate::models::SkillMatch;
+
+/// Options controlling how search queries are matched and displayed
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// Match skill names exactly (case-insensitive) instead of by substring
+    pub exact: bool,
+    /// Treat the query as a case-insensitive regular expression
+    pub regex: bool,
+    /// Only keep directories that contain a SKILL.md (one extra request per match)
+    pub manifest_check: bool,
+    /// Layout of the printed results
+    pub format: OutputFormat,
+    /// Columns shown, in order, by the table format; empty means `DEFAULT_SEARCH_FIELDS`
+    pub fields: Vec<SearchField>,
+    /// How markets that cannot be fetched are reported
+    pub market_errors: MarketErrors,
+    /// Sort results by their last change, newest first, marking skills changed within
+    /// this window as new (one extra request per match)
+    pub new_within: Option<Duration>,
+    /// Names of the markets searched; empty searches every enabled market
+    pub markets: Vec<String>,
+    /// Skills whose name contains one of these (case-insensitive), or matches it as a
+    /// glob when it has `*` or `?`, are left out of the results
+    pub exclude: Vec<String>,
+    /// Only keep skills whose SKILL.md frontmatter has each key with a value containing the
+    /// given one, case-insensitively (one extra request per match)
+    pub manifest_fields: Vec<(String, String)>,
+}
+
+/// Reporting of markets whose listing could not be fetched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MarketErrors {
+    /// One warning line per market
+    #[default]
+    Warn,
+    /// One warning per market with the full error chain
+    Verbose,
+    /// A single line counting the failed markets
+    Summary,
+    /// Nothing
+    Silent,
+}
+
+/// Number of market listings fetched at once unless configured otherwise
+pub const DEFAULT_MAX_CONCURRENCY: usize = 8;
+
+/// Layouts for printing search results
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// One block per skill with its URL
+    #[default]
+    Text,
+    /// One aligned row per skill with the selected fields
+    Table,
+}
+
+/// Columns available in table output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SearchField {
+    Name,
+    Market,
+    Url,
+    Description,
+}
+
+impl SearchField {
+    fn header(&self) -> &'static str {
+        match self {
+            SearchField::Name => "NAME",
+            SearchField::Market => "MARKET",
+            SearchField::Url => "URL",
+            SearchField::Description => "DESCRIPTION",
+        }
+    }
+
+    fn value(&self, skill: &SkillMatch) -> String {
+        match self {
+            SearchField::Name => skill.name.clone(),
+            SearchField::Market => skill.market_name.clone(),
+            SearchField::Url => skill.url.clone(),
+            SearchField::Description => skill.description.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// Columns shown by the table format when no fields are selected
+pub const DEFAULT_SEARCH_FIELDS: [SearchField; 3] =
+    [SearchField::Name, SearchField::Market, SearchField::Url];
+
+/// Result of looking a skill up by name
+pub enum NameLookup {
+    /// Skills whose name equals the requested name
+    Exact(Vec<SkillMatch>),
+    /// No exact match; skills whose name starts with or contains the requested name
+    Similar(Vec<SkillMatch>),
+}
 
 /// Service for finding and searching skills
 pub struct SkillFinder<S: MarketStorage, U: GitHubUrlParser, A: GitHubApiClient> {
     market_service: MarketService<S, U>,
     api_client: A,
+    /// SKILL.md contents keyed by skill URL, `None` when the manifest is unavailable
+    manifests: RefCell<HashMap<String, Option<String>>>,
+    max_concurrency: usize,
 }
 
 impl<S: MarketStorage, U: GitHubUrlParser, A: GitHubApiClient> SkillFinder<S, U, A> {
@@ -16,85 +123,463 @@ impl<S: MarketStorage, U: GitHubUrlParser, A: GitHubApiClient> SkillFinder<S, U,
         Self {
             market_service,
             api_client,
+            manifests: RefCell::new(HashMap::new()),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
         }
     }
 
-    pub fn find_by_name(&self, skill_name: &str) -> Result<Vec<SkillMatch>> {
-        let repositories = self.market_service.get_repositories()?;
+    /// Bounds how many market listings are requested at the same time
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Returns the skill's SKILL.md contents, fetching each manifest at most once
+    pub fn manifest(&self, skill: &SkillMatch) -> Option<String> {
+        if let Some(cached) = self.manifests.borrow().get(&skill.url) {
+            return cached.clone();
+        }
+
+        let path = format!("{}/{}", skill.path, MANIFEST_FILE);
+        let manifest = self.api_client.get_file_contents(&skill.repo, &path).ok();
 
-        if repositories.is_empty() {
-            return Ok(Vec::new());
+        self.manifests
+            .borrow_mut()
+            .insert(skill.url.clone(), manifest.clone());
+        manifest
+    }
+
+    /// Whether the skill's SKILL.md declares every field `options.manifest_fields` asks for
+    fn has_manifest_fields(&self, skill: &SkillMatch, options: &SearchOptions) -> bool {
+        let Some(content) = self.manifest(skill) else {
+            return false;
+        };
+        let fields = manifest::parse_frontmatter(&content);
+        options.manifest_fields.iter().all(|(key, value)| {
+            fields
+                .get(key)
+                .is_some_and(|field| field.to_lowercase().contains(&value.to_lowercase()))
+        })
+    }
+
+    /// Fills in each skill's description from its SKILL.md, leaving it empty when the
+    /// manifest cannot be fetched or declares none
+    pub fn with_descriptions(&self, mut skills: Vec<SkillMatch>) -> Vec<SkillMatch> {
+        for skill in &mut skills {
+            skill.description = self
+                .manifest(skill)
+                .and_then(|content| manifest::description(&content));
         }
+        skills
+    }
+
+    /// Fills in when each skill folder last changed, fetching up to `max_concurrency` at
+    /// once, and orders the skills newest first with unknown times last
+    pub fn with_update_times(&self, skills: Vec<SkillMatch>) -> Result<Vec<SkillMatch>> {
+        let pool = self.thread_pool()?;
+        let api_client = &self.api_client;
+        let times = pool.install(|| {
+            skills
+                .par_iter()
+                .map(|skill| {
+                    api_client
+                        .last_commit_time(&skill.repo, &skill.path)
+                        .ok()
+                        .flatten()
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let mut skills = skills
+            .into_iter()
+            .zip(times)
+            .map(|(skill, updated)| SkillMatch { updated, ..skill })
+            .collect::<Vec<_>>();
+        // `None` sorts before any time, so reversing puts unknown times last
+        skills.sort_by_key(|skill| std::cmp::Reverse(skill.updated));
+        Ok(skills)
+    }
 
+    fn thread_pool(&self) -> Result<rayon::ThreadPool> {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(self.max_concurrency)
+            .build()
+            .context("Failed to start market fetch threads")
+    }
+
+    pub fn find_by_name(&self, skill_name: &str) -> Result<Vec<SkillMatch>> {
+        let skill_name_lower = skill_name.to_lowercase();
+
+        Ok(self
+            .list_skills(MarketErrors::Silent, &[])?
+            .into_iter()
+            .filter(|skill| skill.name.to_lowercase() == skill_name_lower)
+            .collect())
+    }
+
+    /// Looks up a skill by exact name, falling back to skills whose name starts with or
+    /// contains it (prefix matches first) when nothing matches exactly
+    pub fn find_by_name_or_similar(&self, skill_name: &str) -> Result<NameLookup> {
         let skill_name_lower = skill_name.to_lowercase();
-        let mut matches = Vec::new();
+        let skills = self.list_skills(MarketErrors::Silent, &[])?;
+
+        let exact: Vec<SkillMatch> = skills
+            .iter()
+            .filter(|skill| skill.name.to_lowercase() == skill_name_lower)
+            .cloned()
+            .collect();
+
+        if !exact.is_empty() {
+            return Ok(NameLookup::Exact(exact));
+        }
+
+        let (mut similar, contains): (Vec<SkillMatch>, Vec<SkillMatch>) = skills
+            .into_iter()
+            .filter(|skill| skill.name.to_lowercase().contains(&skill_name_lower))
+            .partition(|skill| skill.name.to_lowercase().starts_with(&skill_name_lower));
+        similar.extend(contains);
+
+        Ok(NameLookup::Similar(similar))
+    }
+
+    /// Lists every skill directory across the configured markets, or only those named in
+    /// `markets`, fetching up to `max_concurrency` markets at once. Markets that cannot be
+    /// fetched are skipped and reported as `errors` says.
+    fn list_skills(&self, errors: MarketErrors, markets: &[String]) -> Result<Vec<SkillMatch>> {
+        let repositories = self.market_service.get_repositories_in(markets)?;
+
+        let pool = self.thread_pool()?;
+        // Results keep the market order regardless of which request finishes first
+        let api_client = &self.api_client;
+        let listings = pool.install(|| {
+            repositories
+                .par_iter()
+                .map(|(repo, path, _, _)| api_client.get_directory_contents(repo, path))
+                .collect::<Vec<_>>()
+        });
+
+        let mut skills = Vec::new();
+        let mut failed = 0;
 
-        for (repo, path, base_url, market_name) in repositories {
-            let contents = match self.api_client.get_directory_contents(&repo, &path) {
+        for ((repo, _, base_url, market_name), listing) in repositories.into_iter().zip(listings) {
+            let contents = match listing {
                 Ok(c) => c,
-                Err(_) => continue,
+                Err(e) => {
+                    match errors {
+                        MarketErrors::Warn => {
+                            eprintln!("Warning: Failed to fetch from {}: {}", repo, e)
+                        }
+                        MarketErrors::Verbose => {
+                            eprintln!("Warning: Failed to fetch from {}: {:#}", repo, e)
+                        }
+                        MarketErrors::Summary | MarketErrors::Silent => {}
+                    }
+                    failed += 1;
+                    continue;
+                }
             };
 
             for item in contents {
-                if item.item_type == "dir" && item.name.to_lowercase() == skill_name_lower {
-                    matches.push(SkillMatch {
-                        name: item.name.clone(),
+                if item.item_type == "dir" {
+                    skills.push(SkillMatch {
+                        name: item.name,
                         url: format!("{}/{}", base_url, item.path),
                         market_name: market_name.clone(),
+                        repo: repo.clone(),
+                        path: item.path,
+                        description: None,
+                        updated: None,
                     });
                 }
             }
         }
 
-        Ok(matches)
+        if errors == MarketErrors::Summary && failed > 0 {
+            eprintln!(
+                "Warning: {} market(s) could not be fetched; rerun with --verbose for details",
+                failed
+            );
+        }
+
+        Ok(skills)
     }
 
-    pub fn search(&self, query: &str) -> Result<()> {
-        let repositories = self.market_service.get_repositories()?;
+    pub fn search(&self, query: &str, options: &SearchOptions) -> Result<()> {
+        if let OutputFormat::Table = options.format {
+            let fields = if options.fields.is_empty() {
+                DEFAULT_SEARCH_FIELDS.to_vec()
+            } else {
+                options.fields.clone()
+            };
+
+            let mut results = self.find_matching(query, options)?;
+            if fields.contains(&SearchField::Description) {
+                results = self.with_descriptions(results);
+            }
+            self.display_search_table(&results, query, &fields);
+
+            return Ok(());
+        }
 
         println!("Searching for skills matching '{}'...\n", query);
 
+        let mut results = self.find_matching(query, options)?;
+        if options.new_within.is_some() {
+            results = self.with_update_times(results)?;
+            if !results.is_empty() && results.iter().all(|skill| skill.updated.is_none()) {
+                eprintln!(
+                    "Warning: Could not look up when the skills changed; showing them in market order"
+                );
+            }
+        }
+        self.display_search_results(&results, query, options.new_within);
+
+        Ok(())
+    }
+
+    /// Returns every skill whose name matches `query`, case-insensitively
+    pub fn find_matching(&self, query: &str, options: &SearchOptions) -> Result<Vec<SkillMatch>> {
         let query_lower = query.to_lowercase();
-        let mut all_found_skills = Vec::new();
 
-        for (repo, path, base_url, market_name) in repositories {
-            let contents = match self.api_client.get_directory_contents(&repo, &path) {
-                Ok(c) => c,
-                Err(e) => {
-                    eprintln!("Warning: Failed to fetch from {}: {}", repo, e);
-                    continue;
-                }
-            };
+        // Compiled before any market is fetched so a bad pattern fails immediately
+        let pattern = if options.regex {
+            Some(
+                regex::RegexBuilder::new(query)
+                    .case_insensitive(true)
+                    .build()
+                    .with_context(|| format!("Invalid regular expression '{}'", query))?,
+            )
+        } else {
+            None
+        };
+        let excludes = options
+            .exclude
+            .iter()
+            .map(|exclude| exclude_pattern(exclude))
+            .collect::<Result<Vec<_>>>()?;
 
-            for item in contents {
-                if item.item_type == "dir" && item.name.to_lowercase().contains(&query_lower) {
-                    all_found_skills.push((item, base_url.clone(), market_name.clone()));
+        Ok(self
+            .list_skills(options.market_errors, &options.markets)?
+            .into_iter()
+            .filter(|skill| {
+                let name_lower = skill.name.to_lowercase();
+                if let Some(pattern) = &pattern {
+                    pattern.is_match(&skill.name)
+                } else if options.exact {
+                    name_lower == query_lower
+                } else {
+                    name_lower.contains(&query_lower)
                 }
+            })
+            .filter(|skill| !excludes.iter().any(|exclude| exclude.is_match(&skill.name)))
+            // Checked after name matching so only candidates cost a request
+            .filter(|skill| !options.manifest_check || self.manifest(skill).is_some())
+            .filter(|skill| {
+                options.manifest_fields.is_empty() || self.has_manifest_fields(skill, options)
+            })
+            .collect())
+    }
+
+    /// Counts the skills matching `query` in each searched market, in search order, including
+    /// markets without any match
+    pub fn count_per_market(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<(String, usize)>> {
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for (_, _, _, market_name) in self.market_service.get_repositories_in(&options.markets)? {
+            if !counts.iter().any(|(name, _)| *name == market_name) {
+                counts.push((market_name, 0));
+            }
+        }
+
+        for skill in self.find_matching(query, options)? {
+            if let Some((_, count)) = counts
+                .iter_mut()
+                .find(|(name, _)| *name == skill.market_name)
+            {
+                *count += 1;
             }
         }
 
-        self.display_search_results(&all_found_skills, query);
+        Ok(counts)
+    }
+
+    /// Returns the single skill matching `query`, asking the user to pick when there are
+    /// several
+    pub fn find_one<I: UserInteraction>(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+        ui: &I,
+    ) -> Result<SkillMatch> {
+        let matches = self.find_matching(query, options)?;
+
+        match matches.len() {
+            0 => Err(anyhow!("No skills found matching '{}'", query)),
+            1 => Ok(matches.into_iter().next().unwrap()),
+            _ => Ok(ui.select_skill(&matches)?.clone()),
+        }
+    }
+
+    /// Lists the configured markets whose name or URL matches `query`
+    pub fn search_markets(&self, query: &str) -> Result<()> {
+        let markets = self.market_service.find_markets(query)?;
+
+        if markets.is_empty() {
+            println!("No markets found matching '{}'", query);
+        } else {
+            println!("Found {} market(s):\n", markets.len());
+            for market in markets {
+                if market.enabled {
+                    println!("  • {}", market.name);
+                } else {
+                    println!("  • {} (disabled)", market.name);
+                }
+                println!("    URL: {}", market.url);
+                println!();
+            }
+        }
 
         Ok(())
     }
 
-    fn display_search_results(&self, results: &[(GitHubContent, String, String)], query: &str) {
+    /// Prints one block per skill; with `new_within`, also when it last changed and whether
+    /// that is recent enough to count as new
+    fn display_search_results(
+        &self,
+        results: &[SkillMatch],
+        query: &str,
+        new_within: Option<Duration>,
+    ) {
         if results.is_empty() {
             println!("No skills found matching '{}'", query);
         } else {
             println!("Found {} skill(s):\n", results.len());
-            for (skill, base_url, market_name) in results {
-                println!("  • {} ({})", skill.name, market_name);
-                println!("    URL: {}/{}", base_url, skill.path);
+            for skill in results {
+                let age = skill.updated.map(|updated| {
+                    SystemTime::now()
+                        .duration_since(updated)
+                        .unwrap_or_default()
+                });
+                let is_new =
+                    matches!((age, new_within), (Some(age), Some(window)) if age <= window);
+
+                if is_new {
+                    println!("  • {} ({}) [new]", skill.name, skill.market_name);
+                } else {
+                    println!("  • {} ({})", skill.name, skill.market_name);
+                }
+                println!("    URL: {}", skill.url);
+                if let Some(age) = age {
+                    println!("    Updated: {}", format_age(age));
+                }
                 println!();
             }
         }
     }
+
+    /// Prints one row per skill with the given columns, padded to line up
+    fn display_search_table(&self, results: &[SkillMatch], query: &str, fields: &[SearchField]) {
+        if results.is_empty() {
+            println!("No skills found matching '{}'", query);
+            return;
+        }
+
+        let rows: Vec<Vec<String>> = results
+            .iter()
+            .map(|skill| fields.iter().map(|field| field.value(skill)).collect())
+            .collect();
+
+        let widths: Vec<usize> = fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                rows.iter()
+                    .map(|row| row[i].chars().count())
+                    .chain([field.header().len()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let print_row = |cells: Vec<&str>| {
+            let line = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ");
+            println!("{}", line.trim_end());
+        };
+
+        print_row(fields.iter().map(|field| field.header()).collect());
+        for row in &rows {
+            print_row(row.iter().map(String::as_str).collect());
+        }
+    }
+}
+
+/// Parses `key=value` arguments of `--manifest-field`
+pub fn parse_manifest_fields(fields: &[String]) -> Result<Vec<(String, String)>> {
+    fields
+        .iter()
+        .map(|field| match field.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                Ok((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(anyhow!(
+                "Invalid --manifest-field '{}': expected 'key=value'",
+                field
+            )),
+        })
+        .collect()
+}
+
+/// Compiles a `--exclude` value into a case-insensitive matcher: a glob over the whole
+/// name when it contains glob syntax, otherwise a substring
+fn exclude_pattern(exclude: &str) -> Result<GlobMatcher> {
+    let glob = if is_glob(exclude) {
+        exclude.to_string()
+    } else {
+        format!("*{}*", globset::escape(exclude))
+    };
+
+    Ok(GlobBuilder::new(&glob)
+        .case_insensitive(true)
+        .build()
+        .with_context(|| format!("Invalid --exclude pattern '{}'", exclude))?
+        .compile_matcher())
+}
+
+/// Whether `pattern` uses glob syntax: `*`, `?`, `[...]` classes or `{a,b}` alternatives
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', '{'])
+}
+
+/// Opens `url` in the default browser, failing with the URL when no browser can be
+/// launched (e.g. over SSH or in a container)
+pub fn open_in_browser(url: &str) -> Result<()> {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        return Err(anyhow!(
+            "No graphical session to open a browser in; visit {} instead",
+            url
+        ));
+    }
+
+    open::that(url).with_context(|| format!("Failed to open {} in a browser", url))?;
+    eprintln!("Opened {}", url);
+    Ok(())
 }
 
 /// Trait for user interaction
 pub trait UserInteraction {
     fn select_skill<'a>(&self, matches: &'a [SkillMatch]) -> Result<&'a SkillMatch>;
+
+    /// Asks `question`, returning whether the reply was `answer`
+    fn confirm(&self, question: &str, answer: &str) -> Result<bool>;
 }
 
 /// Console-based user interaction
@@ -102,29 +587,84 @@ pub struct ConsoleUserInteraction;
 
 impl UserInteraction for ConsoleUserInteraction {
     fn select_skill<'a>(&self, matches: &'a [SkillMatch]) -> Result<&'a SkillMatch> {
-        println!("Multiple skills found. Please select one:");
+        eprintln!("Multiple skills found. Please select one:");
         for (i, skill) in matches.iter().enumerate() {
-            println!("  {}. {} ({})", i + 1, skill.name, skill.market_name);
+            match &skill.description {
+                Some(description) => eprintln!(
+                    "  {}. {} ({}) - {}",
+                    i + 1,
+                    skill.name,
+                    skill.market_name,
+                    description
+                ),
+                None => eprintln!("  {}. {} ({})", i + 1, skill.name, skill.market_name),
+            }
         }
 
-        print!("\nEnter your choice (1-{}): ", matches.len());
-        io::stdout().flush()?;
+        // Piped input is read once so scripts fail instead of hanging; a person
+        // mistyping gets asked again
+        let interactive = io::stdin().is_terminal();
+        let mut lines = io::stdin().lock().lines();
+        loop {
+            eprint!("\nEnter your choice (1-{}): ", matches.len());
+            io::stderr().flush()?;
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+            let line = if interactive {
+                lines.next()
+            } else {
+                lines.find(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+            };
+            if !interactive {
+                // Echo the piped answer so the transcript reads like an interactive one
+                eprintln!(
+                    "{}",
+                    line.as_ref()
+                        .and_then(|l| l.as_ref().ok())
+                        .map_or("", |l| l.trim())
+                );
+            }
+            let Some(line) = line else {
+                return Err(anyhow!(
+                    "No selection given on stdin; pipe a number (e.g. `echo 2 | skills install ...`) or pass --select"
+                ));
+            };
+            let line = line?;
 
-        let choice: usize = input
-            .trim()
-            .parse()
-            .map_err(|_| anyhow::anyhow!("Invalid input, please enter a number"))?;
+            let choice = line
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|choice| (1..=matches.len()).contains(choice));
 
-        if choice < 1 || choice > matches.len() {
-            return Err(anyhow::anyhow!(
-                "Invalid choice, must be between 1 and {}",
-                matches.len()
-            ));
+            match choice {
+                Some(choice) => return Ok(&matches[choice - 1]),
+                None if interactive => {
+                    eprintln!("Please enter a number between 1 and {}", matches.len());
+                }
+                None => {
+                    return Err(anyhow!(
+                        "Invalid selection '{}' on stdin, must be a number between 1 and {}",
+                        line.trim(),
+                        matches.len()
+                    ));
+                }
+            }
         }
+    }
+    fn confirm(&self, question: &str, answer: &str) -> Result<bool> {
+        eprint!("{} ", question);
+        io::stderr().flush()?;
 
-        Ok(&matches[choice - 1])
+        let Some(line) = io::stdin().lock().lines().next() else {
+            return Err(anyhow!(
+                "No answer given on stdin; pipe one (e.g. `echo {} | skills ...`) or pass --yes",
+                answer
+            ));
+        };
+        let line = line?;
+        if !io::stdin().is_terminal() {
+            eprintln!("{}", line.trim());
+        }
+        Ok(line.trim().eq_ignore_ascii_case(answer))
     }
 }